@@ -0,0 +1,122 @@
+use crate::{ProgramState, prelude::*, search::SortOrder};
+use anyhow::anyhow;
+use colored::Colorize;
+use std::collections::HashMap;
+
+/// Every package published under a namespace, most downloaded first. Namespaces are matched
+/// regardless of case.
+pub fn packages_by<'a>(
+    packages: &'a HashMap<NamespacedPackage, SearchablePackage>,
+    namespace: &str,
+) -> Vec<&'a SearchablePackage> {
+    let mut published = packages
+        .values()
+        .filter(|package| package.namespace.eq_ignore_ascii_case(namespace))
+        .collect::<Vec<_>>();
+    published.sort_by(|a, b| SortOrder::Downloads.compare(a, b));
+    published
+}
+
+/// A package of the author as listed for installation
+struct AuthorPackage<'a> {
+    package: &'a SearchablePackage,
+    installed: bool,
+}
+
+impl std::fmt::Display for AuthorPackage<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.package.table_row(SortOrder::Downloads))?;
+        if self.package.is_deprecated {
+            write!(f, " {}", "[deprecated]".red())?;
+        }
+        if self.installed {
+            write!(f, " {}", "[installed]".green())?;
+        }
+        Ok(())
+    }
+}
+
+/// Lists the packages of an author along with their totals, letting the user pick any number of
+/// them to review and install together
+pub async fn view(
+    program_state: &mut ProgramState,
+    api: &thunderstore::Client,
+    namespace: &str,
+) -> anyhow::Result<()> {
+    let published = packages_by(&program_state.packages, namespace);
+    if published.is_empty() {
+        return Err(anyhow!(
+            "No packages were found for the author {}",
+            namespace
+        ));
+    }
+
+    clearscreen::clear()?;
+    println!(
+        "{}: {} packages, {} downloads, {} rating in total",
+        published[0].namespace.bold(),
+        published.len(),
+        published
+            .iter()
+            .map(|package| package.total_downloads() as u64)
+            .sum::<u64>(),
+        published
+            .iter()
+            .map(|package| package.rating_score as u64)
+            .sum::<u64>()
+    );
+
+    let (_, height) = term_size::dimensions().unwrap_or((60, 60));
+    let options = published
+        .iter()
+        .map(|package| AuthorPackage {
+            package,
+            installed: program_state
+                .lockfile
+                .get(&NamespacedPackage::from(*package))
+                .is_some(),
+        })
+        .collect::<Vec<_>>();
+
+    let Some(selected) = inquire::MultiSelect::new(
+        "Press <space> to pick packages and <enter> to install them, or <esc> to go back",
+        options,
+    )
+    .with_page_size(height.saturating_sub(4).max(1))
+    .with_help_message(&SearchablePackage::table_header(SortOrder::Downloads))
+    .prompt_skippable()?
+    else {
+        return Ok(());
+    };
+
+    let to_install = selected
+        .iter()
+        .filter_map(|selected| {
+            selected
+                .package
+                .versions
+                .iter()
+                .max_by_key(|version| version.number.clone())
+                .cloned()
+        })
+        .collect::<Vec<_>>();
+    if to_install.is_empty() {
+        return Ok(());
+    }
+
+    crate::install::review_and_install(program_state, api, &to_install).await
+}
+
+/// Opens the author view for a namespace from the command line
+pub async fn perform_author(
+    mut program_state: ProgramState,
+    api: &thunderstore::Client,
+    namespace: &str,
+) -> anyhow::Result<()> {
+    if program_state.packages.is_empty() {
+        println!("Refreshing packages...");
+        program_state.refresh_packages(api).await?;
+    }
+
+    view(&mut program_state, api, namespace).await
+}
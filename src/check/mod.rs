@@ -0,0 +1,210 @@
+use crate::{ProgramState, prelude::*};
+use anyhow::anyhow;
+use colored::Colorize;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use thunderstore::{VersionIdent, models::PackageVersionV1};
+
+/// A version counts as deprecated when it has been deactivated, or its whole package deprecated
+fn is_deprecated(package: &SearchablePackage, version: &PackageVersionV1) -> bool {
+    package.is_deprecated || !version.is_active
+}
+
+/// Checks a single requirement against the index, returning the version it resolves to if there
+/// are no problems with it
+fn check_requirement<'a>(
+    program_state: &'a ProgramState,
+    namespaced: &NamespacedPackage,
+    requirement: &Requirement,
+    problems: &mut Vec<String>,
+) -> Option<&'a PackageVersionV1> {
+    let Some(package) = program_state.packages.get(namespaced) else {
+        problems.push(format!("{} no longer exists on Thunderstore", namespaced));
+        return None;
+    };
+
+    let matching = package
+        .versions
+        .iter()
+        .filter(|version| requirement.version.matches(&version.number))
+        .collect::<Vec<_>>();
+
+    if matching.is_empty() {
+        problems.push(format!(
+            "{} {} does not match any published version",
+            namespaced, requirement.version
+        ));
+        return None;
+    }
+
+    if matching
+        .iter()
+        .all(|version| is_deprecated(package, version))
+    {
+        problems.push(format!(
+            "{} {} only matches deprecated versions",
+            namespaced, requirement.version
+        ));
+    }
+
+    matching
+        .into_iter()
+        .max_by_key(|version| version.number.clone())
+}
+
+/// Reports packages which two dependents need at incompatible versions, where the newer minimum
+/// is a major version the older one doesn't accept
+fn check_demands(
+    namespaced: &NamespacedPackage,
+    demands: &[(&VersionIdent, semver::Version)],
+    problems: &mut Vec<String>,
+) {
+    let Some((newest_dependent, newest)) = demands.iter().max_by_key(|(_, minimum)| minimum) else {
+        return;
+    };
+
+    for (dependent, minimum) in demands {
+        let compatible = semver::VersionReq::parse(&format!("^{}", minimum))
+            .is_ok_and(|requirement| requirement.matches(newest));
+        if !compatible {
+            problems.push(format!(
+                "{} needs {} {}, but {} needs {} {}, which is not compatible",
+                dependent, namespaced, minimum, newest_dependent, namespaced, newest
+            ));
+        }
+    }
+}
+
+/// Walks the dependencies of every resolved requirement, reporting dependencies which are missing
+/// from the index, dependencies whose minimum version is ruled out by an explicit requirement and
+/// packages which several dependencies need at incompatible versions
+fn check_dependencies(
+    program_state: &ProgramState,
+    resolved: Vec<&PackageVersionV1>,
+    problems: &mut Vec<String>,
+) {
+    let mut seen = resolved
+        .iter()
+        .map(|version| NamespacedPackage::from(&version.ident))
+        .collect::<HashSet<_>>();
+    let mut queue = resolved.into_iter().collect::<VecDeque<_>>();
+    // The minimum version each dependent in the resolved set asks of a package
+    let mut demands = BTreeMap::<_, Vec<_>>::new();
+
+    while let Some(version) = queue.pop_front() {
+        for dependency in &version.dependencies {
+            let namespaced = NamespacedPackage::from(dependency);
+            let minimum = dependency.parsed_version();
+            demands
+                .entry(namespaced.clone())
+                .or_default()
+                .push((&version.ident, minimum.clone()));
+
+            let Some(package) = program_state.packages.get(&namespaced) else {
+                problems.push(format!(
+                    "{} depends on {}, which does not exist on Thunderstore",
+                    version.ident, namespaced
+                ));
+                continue;
+            };
+
+            if let Some(requirement) = program_state.requirements.get(&namespaced)
+                && !package.versions.iter().any(|candidate| {
+                    candidate.number >= minimum && requirement.version.matches(&candidate.number)
+                })
+            {
+                problems.push(format!(
+                    "{} needs {} >= {}, but it is required as {}",
+                    version.ident, namespaced, minimum, requirement.version
+                ));
+            }
+
+            if !seen.insert(namespaced) {
+                continue;
+            }
+            if let Some(latest) = package
+                .versions
+                .iter()
+                .max_by_key(|candidate| candidate.number.clone())
+            {
+                queue.push_back(latest);
+            }
+        }
+    }
+
+    for (namespaced, demands) in &demands {
+        check_demands(namespaced, demands, problems);
+    }
+}
+
+/// Validates every requirement against the cached Thunderstore index, failing if any problems
+/// were found
+pub async fn perform_check(
+    mut program_state: ProgramState,
+    api: &thunderstore::Client,
+    update_cache: bool,
+) -> anyhow::Result<()> {
+    if update_cache || program_state.packages.is_empty() {
+        println!("Refreshing packages...");
+        program_state.refresh_packages(api).await?;
+    }
+
+    let mut requirements = program_state.requirements.iter().collect::<Vec<_>>();
+    requirements.sort_by_key(|(namespaced, _)| *namespaced);
+
+    let mut problems = Vec::new();
+    let mut resolved = Vec::new();
+    for (namespaced, requirement) in requirements {
+        if let Some(version) =
+            check_requirement(&program_state, namespaced, requirement, &mut problems)
+        {
+            resolved.push(version);
+        }
+    }
+    check_dependencies(&program_state, resolved, &mut problems);
+
+    if problems.is_empty() {
+        println!("{}", "All requirements are valid".green());
+        return Ok(());
+    }
+
+    for problem in &problems {
+        println!("{} {}", "error:".red(), problem);
+    }
+    Err(anyhow!(
+        "{} problem(s) were found with the requirements",
+        problems.len()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dependencies_on_different_major_versions_conflict() {
+        let namespaced = NamespacedPackage::value_parser("A/Z").unwrap();
+        let first = VersionIdent::new("A", "X", "1.0.0");
+        let second = VersionIdent::new("A", "Y", "1.0.0");
+        let third = VersionIdent::new("A", "W", "1.0.0");
+        let version = |version: &str| semver::Version::parse(version).unwrap();
+
+        let mut problems = Vec::new();
+        check_demands(
+            &namespaced,
+            &[
+                (&first, version("1.2.0")),
+                (&second, version("1.4.1")),
+                (&third, version("2.0.0")),
+            ],
+            &mut problems,
+        );
+
+        assert_eq!(
+            problems,
+            [
+                "A-X-1.0.0 needs A/Z 1.2.0, but A-W-1.0.0 needs A/Z 2.0.0, which is not compatible",
+                "A-Y-1.0.0 needs A/Z 1.4.1, but A-W-1.0.0 needs A/Z 2.0.0, which is not compatible",
+            ]
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{ProgramState, prelude::*, utils};
+use colored::Colorize;
+use pad::{Alignment, PadStr};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// A package which depends on another package in at least one of its versions
+pub struct Dependent<'a> {
+    pub package: &'a SearchablePackage,
+    /// Whether the newest version of the package still has the dependency
+    pub latest: bool,
+}
+
+/// Every package in the index keyed by the packages they depend on, across all of their versions
+pub struct ReverseDependencies(HashMap<NamespacedPackage, BTreeMap<NamespacedPackage, bool>>);
+
+impl ReverseDependencies {
+    pub fn build(packages: &HashMap<NamespacedPackage, SearchablePackage>) -> Self {
+        let mut index: HashMap<NamespacedPackage, BTreeMap<NamespacedPackage, bool>> =
+            HashMap::new();
+
+        for (namespaced, package) in packages {
+            let latest = package
+                .versions
+                .iter()
+                .max_by_key(|version| version.number.clone());
+
+            for version in &package.versions {
+                let is_latest = latest.is_some_and(|latest| latest == version);
+                for dependency in &version.dependencies {
+                    let in_latest = index
+                        .entry(NamespacedPackage::from(dependency))
+                        .or_default()
+                        .entry(namespaced.clone())
+                        .or_default();
+                    *in_latest |= is_latest;
+                }
+            }
+        }
+
+        Self(index)
+    }
+
+    /// The packages depending on `target`, most downloaded first
+    pub fn dependents_of<'a>(
+        &self,
+        packages: &'a HashMap<NamespacedPackage, SearchablePackage>,
+        target: &NamespacedPackage,
+    ) -> Vec<Dependent<'a>> {
+        let mut dependents = self
+            .0
+            .get(target)
+            .into_iter()
+            .flatten()
+            .filter_map(|(namespaced, latest)| {
+                Some(Dependent {
+                    package: packages.get(namespaced)?,
+                    latest: *latest,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        dependents.sort_by_key(|dependent| std::cmp::Reverse(dependent.package.total_downloads()));
+        dependents
+    }
+}
+
+/// Lists every package which depends on a mod, optionally only those installed on this server
+pub async fn perform_dependents(
+    mut program_state: ProgramState,
+    api: &thunderstore::Client,
+    target: &NamespacedPackage,
+    installed_only: bool,
+) -> anyhow::Result<()> {
+    if program_state.packages.is_empty() {
+        println!("Refreshing packages...");
+        program_state.refresh_packages(api).await?;
+    }
+
+    let installed = utils::get_installed_mods(&program_state)?
+        .into_iter()
+        .map(|installed| installed.namespaced)
+        .collect::<HashSet<_>>();
+
+    let dependents = program_state
+        .reverse_dependencies()
+        .dependents_of(&program_state.packages, target)
+        .into_iter()
+        .filter(|dependent| {
+            !installed_only || installed.contains(&NamespacedPackage::from(dependent.package))
+        })
+        .collect::<Vec<_>>();
+
+    if dependents.is_empty() {
+        println!(
+            "No {}packages depend on {}",
+            if installed_only { "installed " } else { "" },
+            target
+        );
+        return Ok(());
+    }
+
+    for dependent in dependents {
+        let namespaced = NamespacedPackage::from(dependent.package);
+        let mut notes = Vec::new();
+        if !dependent.latest {
+            notes.push("older versions only".yellow().to_string());
+        }
+        if installed.contains(&namespaced) {
+            notes.push("installed".green().to_string());
+        }
+
+        println!(
+            "{}{}{}",
+            namespaced
+                .to_string()
+                .pad_to_width_with_alignment(50, Alignment::Left),
+            format!("{} downloads", dependent.package.total_downloads())
+                .pad_to_width_with_alignment(22, Alignment::Left),
+            notes.join(", ")
+        );
+    }
+
+    Ok(())
+}
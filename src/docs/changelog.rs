@@ -0,0 +1,143 @@
+use super::Document;
+use thunderstore::VersionIdent;
+
+/// Parses a version as written in a changelog, such as `v1.2` or `1.2.0`
+fn parse_version(word: &str) -> Option<semver::Version> {
+    let word = word.trim_start_matches(['v', 'V']).trim_end_matches('.');
+    let parts = word.split('.').count();
+    if !(2..=3).contains(&parts) || !word.starts_with(|char: char| char.is_ascii_digit()) {
+        return None;
+    }
+    let padded = if parts == 2 {
+        format!("{}.0", word)
+    } else {
+        word.to_owned()
+    };
+    semver::Version::parse(&padded).ok()
+}
+
+/// Finds the version a changelog heading is for, such as `## [1.2.0] - 2024-05-01` or
+/// `### v1.2`. Changelogs without headings often write each version in bold instead, so a line
+/// which is all bold counts as a heading when it holds nothing but a version, such as `**1.2.0**`.
+fn heading_version(line: &str) -> Option<semver::Version> {
+    let line = line.trim();
+
+    if let Some(bold) = line.strip_prefix("**") {
+        let version = bold.strip_suffix("**")?.trim();
+        let version = version
+            .strip_prefix('[')
+            .and_then(|version| version.strip_suffix(']'))
+            .unwrap_or(version);
+        return parse_version(version);
+    }
+
+    if !line.starts_with('#') {
+        return None;
+    }
+    line.split(|char: char| !(char.is_ascii_alphanumeric() || char == '.' || char == '-'))
+        .find_map(parse_version)
+}
+
+/// Keeps the entries of a changelog for versions newer than `installed`, up to and including
+/// `target`. `None` if the changelog doesn't have a heading per version.
+pub fn entries_between(
+    markdown: &str,
+    installed: &semver::Version,
+    target: &semver::Version,
+) -> Option<String> {
+    let mut entries = String::new();
+    let mut found_heading = false;
+    let mut keeping = false;
+
+    for line in markdown.lines() {
+        if let Some(version) = heading_version(line) {
+            found_heading = true;
+            keeping = version > *installed && version <= *target;
+        }
+        if keeping {
+            entries.push_str(line);
+            entries.push('\n');
+        }
+    }
+
+    found_heading.then_some(entries)
+}
+
+/// What the changelog of an update target says about the versions since the installed one
+pub enum Changes {
+    /// The target was published without a changelog
+    NoChangelog,
+    /// The changelog doesn't have a heading per version, so the changes can't be picked out of it
+    NoHeadings,
+    /// The entries for the versions since the installed one, which may be empty
+    Entries(String),
+}
+
+/// Gets the changes made between the installed version and an update target, from the target's
+/// changelog
+pub async fn changes_since(
+    api: &thunderstore::Client,
+    installed: &semver::Version,
+    target: &VersionIdent,
+) -> anyhow::Result<Changes> {
+    let Some(markdown) = super::fetch(api, target, Document::Changelog).await? else {
+        return Ok(Changes::NoChangelog);
+    };
+
+    Ok(
+        match entries_between(&markdown, installed, &target.parsed_version()) {
+            Some(entries) => Changes::Entries(entries),
+            None => Changes::NoHeadings,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(version: &str) -> semver::Version {
+        semver::Version::parse(version).unwrap()
+    }
+
+    #[test]
+    fn headings_name_their_version() {
+        assert_eq!(
+            heading_version("## [1.2.0] - 2024-05-01"),
+            Some(version("1.2.0"))
+        );
+        assert_eq!(heading_version("### v1.2"), Some(version("1.2.0")));
+        assert_eq!(heading_version("**1.2.0**"), Some(version("1.2.0")));
+        assert_eq!(heading_version("  **[v1.3]**  "), Some(version("1.3.0")));
+        assert_eq!(heading_version("# Changelog"), None);
+        assert_eq!(heading_version("1.2.0"), None);
+    }
+
+    #[test]
+    fn bold_lines_are_only_headings_when_they_hold_just_a_version() {
+        assert_eq!(heading_version("**Fixed** a crash added in 1.1.0"), None);
+        assert_eq!(heading_version("**Requires 1.1.0 or newer**"), None);
+        assert_eq!(heading_version("**1.2.0** - the big update"), None);
+    }
+
+    #[test]
+    fn entries_are_kept_for_the_versions_since_the_installed_one() {
+        let markdown = "\
+# Changelog
+**1.2.0**
+- **Fixed** 1.1.0 saves
+**1.1.0**
+- Added boats
+**1.0.0**
+- Released
+";
+        assert_eq!(
+            entries_between(markdown, &version("1.0.0"), &version("1.2.0")).unwrap(),
+            "**1.2.0**\n- **Fixed** 1.1.0 saves\n**1.1.0**\n- Added boats\n"
+        );
+        assert_eq!(
+            entries_between("- Fixed a crash\n", &version("1.0.0"), &version("1.2.0")),
+            None
+        );
+    }
+}
@@ -0,0 +1,341 @@
+use colored::Colorize;
+
+/// The number of characters of a line which are visible in the terminal, skipping the escape
+/// sequences used for colours
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for char in text.chars() {
+        match (in_escape, char) {
+            (false, '\x1b') => in_escape = true,
+            (false, _) => width += 1,
+            (true, 'm') => in_escape = false,
+            (true, _) => {}
+        }
+    }
+    width
+}
+
+/// Wraps styled text to the width of the terminal. The first line starts with `first_prefix`, and
+/// the lines it wraps onto with `rest_prefix`.
+fn wrap(text: &str, width: usize, first_prefix: &str, rest_prefix: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = first_prefix.to_owned();
+    let mut line_width = visible_width(first_prefix);
+    let mut line_empty = true;
+
+    for word in text.split(' ').filter(|word| !word.is_empty()) {
+        let word_width = visible_width(word);
+        if !line_empty && line_width + 1 + word_width > width {
+            lines.push(std::mem::replace(&mut line, rest_prefix.to_owned()));
+            line_width = visible_width(rest_prefix);
+            line_empty = true;
+        }
+        if !line_empty {
+            line.push(' ');
+            line_width += 1;
+        }
+        line.push_str(word);
+        line_width += word_width;
+        line_empty = false;
+    }
+
+    lines.push(line);
+    lines
+}
+
+/// Breaks a line of code into pieces no wider than `width`. Code keeps its spacing, so it is cut
+/// anywhere rather than between words.
+fn wrap_code(code: &str, width: usize) -> Vec<String> {
+    let chars = code.replace('\t', "    ").chars().collect::<Vec<_>>();
+    if chars.is_empty() {
+        return vec![String::new()];
+    }
+    chars
+        .chunks(width.max(1))
+        .map(|piece| piece.iter().collect())
+        .collect()
+}
+
+/// Removes HTML tags, which many READMEs use for images and alignment
+fn strip_html(line: &str) -> String {
+    let mut stripped = String::new();
+    let mut rest = line;
+    while let Some(start) = rest.find('<') {
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        // Only strip things which look like tags, so that `a < b` survives
+        let tag = &rest[start + 1..start + end];
+        if tag.is_empty() || !tag.starts_with(|char: char| char.is_alphabetic() || char == '/') {
+            stripped.push_str(&rest[..start + 1]);
+            rest = &rest[start + 1..];
+            continue;
+        }
+        stripped.push_str(&rest[..start]);
+        rest = &rest[start + end + 1..];
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+/// Finds `pattern` in `chars` at or after `from`
+fn find(chars: &[char], from: usize, pattern: &str) -> Option<usize> {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    (from..chars.len()).find(|start| chars[*start..].starts_with(&pattern))
+}
+
+/// Parses a `[text](url)` link starting at `start`, returning its text, url and the position
+/// after it
+fn parse_link(chars: &[char], start: usize) -> Option<(String, String, usize)> {
+    let text_end = find(chars, start + 1, "]")?;
+    if chars.get(text_end + 1) != Some(&'(') {
+        return None;
+    }
+    let url_end = find(chars, text_end + 2, ")")?;
+    Some((
+        chars[start + 1..text_end].iter().collect(),
+        chars[text_end + 2..url_end].iter().collect(),
+        url_end + 1,
+    ))
+}
+
+/// Styles the inline markdown of a line: code, bold, italics, links and images
+fn render_inline(text: &str) -> String {
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut rendered = String::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        let rest = &chars[index..];
+
+        if rest[0] == '`'
+            && let Some(end) = find(&chars, index + 1, "`")
+        {
+            let code = chars[index + 1..end].iter().collect::<String>();
+            rendered.push_str(&code.yellow().to_string());
+            index = end + 1;
+            continue;
+        }
+
+        if rest.starts_with(&['!', '['])
+            && let Some((alt, _, end)) = parse_link(&chars, index + 1)
+        {
+            rendered.push_str(&format!("[image: {}]", alt).dimmed().to_string());
+            index = end;
+            continue;
+        }
+
+        if rest[0] == '['
+            && let Some((text, url, end)) = parse_link(&chars, index)
+        {
+            if text.is_empty() || text == url {
+                rendered.push_str(&url.blue().underline().to_string());
+            } else {
+                rendered.push_str(&format!(
+                    "{} ({})",
+                    render_inline(&text).blue().underline(),
+                    url.dimmed()
+                ));
+            }
+            index = end;
+            continue;
+        }
+
+        if (rest.starts_with(&['*', '*']) || rest.starts_with(&['_', '_']))
+            && let Some(end) = find(&chars, index + 2, &rest[..2].iter().collect::<String>())
+        {
+            let bold = chars[index + 2..end].iter().collect::<String>();
+            rendered.push_str(&render_inline(&bold).bold().to_string());
+            index = end + 2;
+            continue;
+        }
+
+        // Underscores are left alone, as they are common within mod names
+        if rest[0] == '*'
+            && rest.get(1).is_some_and(|next| !next.is_whitespace())
+            && let Some(end) = find(&chars, index + 1, "*")
+        {
+            let italic = chars[index + 1..end].iter().collect::<String>();
+            rendered.push_str(&render_inline(&italic).italic().to_string());
+            index = end + 1;
+            continue;
+        }
+
+        rendered.push(rest[0]);
+        index += 1;
+    }
+
+    rendered
+}
+
+/// Splits a list item into its marker and text, for `- item`, `* item`, `+ item` and `1. item`
+fn parse_list_item(line: &str) -> Option<(String, &str)> {
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(text) = line.strip_prefix(bullet) {
+            return Some(("•".to_owned(), text));
+        }
+    }
+
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0
+        && let Some(text) = line[digits..]
+            .strip_prefix(". ")
+            .or(line[digits..].strip_prefix(") "))
+    {
+        return Some((format!("{}.", &line[..digits]), text));
+    }
+
+    None
+}
+
+/// Renders markdown as styled lines no wider than `width`. Headings, lists, quotes, code blocks,
+/// links and emphasis are styled, and HTML is left out.
+pub fn render(markdown: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_code = false;
+
+    for raw in markdown.lines() {
+        let raw = raw.trim_end();
+
+        if raw.trim_start().starts_with("```") || raw.trim_start().starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            lines.extend(
+                wrap_code(raw, width.saturating_sub(2))
+                    .into_iter()
+                    .map(|piece| format!("{} {}", "│".dimmed(), piece.yellow())),
+            );
+            continue;
+        }
+
+        let line = strip_html(raw);
+        let trimmed = line.trim_start();
+        let indent = " ".repeat(line.len() - trimmed.len());
+
+        if trimmed.is_empty() {
+            // Runs of blank lines, including those left by stripped HTML, are collapsed
+            if lines.last().is_some_and(|last| !last.is_empty()) {
+                lines.push(String::new());
+            }
+            continue;
+        }
+
+        if trimmed.len() >= 3
+            && trimmed.chars().all(|char| "-*_=".contains(char))
+            && trimmed
+                .chars()
+                .all(|char| char == trimmed.chars().next().unwrap())
+        {
+            lines.push("─".repeat(width).dimmed().to_string());
+            continue;
+        }
+
+        let level = trimmed.chars().take_while(|char| *char == '#').count();
+        if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+            let title = render_inline(trimmed[level..].trim().trim_end_matches('#').trim());
+            if lines.last().is_some_and(|last| !last.is_empty()) {
+                lines.push(String::new());
+            }
+            let title = match level {
+                1 => title.bold().underline().cyan(),
+                2 => title.bold().cyan(),
+                _ => title.bold(),
+            };
+            lines.extend(wrap(&title.to_string(), width, "", ""));
+            continue;
+        }
+
+        if let Some(quote) = trimmed.strip_prefix('>') {
+            let prefix = format!("{}{} ", indent, "│".dimmed());
+            let quote = render_inline(quote.trim()).italic().to_string();
+            lines.extend(wrap(&quote, width, &prefix, &prefix));
+            continue;
+        }
+
+        if let Some((marker, text)) = parse_list_item(trimmed) {
+            let first_prefix = format!("{}{} ", indent, marker);
+            let rest_prefix = " ".repeat(first_prefix.chars().count());
+            lines.extend(wrap(
+                &render_inline(text),
+                width,
+                &first_prefix,
+                &rest_prefix,
+            ));
+            continue;
+        }
+
+        lines.extend(wrap(&render_inline(trimmed), width, &indent, &indent));
+    }
+
+    while lines.last().is_some_and(String::is_empty) {
+        lines.pop();
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(markdown: &str, width: usize) -> Vec<String> {
+        colored::control::set_override(false);
+        render(markdown, width)
+    }
+
+    #[test]
+    fn headings_are_set_apart() {
+        assert_eq!(
+            plain("# Title #\nSome text\n## Changes\n### 1.0.0", 40),
+            ["Title", "Some text", "", "Changes", "", "1.0.0"]
+        );
+        // Without a space it is not a heading
+        assert_eq!(plain("#hashtag", 40), ["#hashtag"]);
+    }
+
+    #[test]
+    fn list_items_wrap_under_their_text() {
+        assert_eq!(
+            plain("- first item that wraps\n  * nested\n1. one\n2) two", 12),
+            [
+                "• first item",
+                "  that wraps",
+                "  • nested",
+                "1. one",
+                "2. two"
+            ]
+        );
+    }
+
+    #[test]
+    fn links_and_images_show_their_text() {
+        assert_eq!(
+            plain(
+                "See [the docs](https://example.com) or https://example.com\n\
+                 [https://example.com](https://example.com) ![logo](logo.png)",
+                80
+            ),
+            [
+                "See the docs (https://example.com) or https://example.com",
+                "https://example.com [image: logo]"
+            ]
+        );
+    }
+
+    #[test]
+    fn code_blocks_keep_their_spacing_and_fit_the_width() {
+        assert_eq!(
+            plain(
+                "```rust\nfn main() {\n    run();\n}\n```\n`inline` *code*",
+                40
+            ),
+            ["│ fn main() {", "│     run();", "│ }", "inline code"]
+        );
+        assert_eq!(
+            plain("```\nlet configuration = 1;\n```", 12),
+            ["│ let config", "│ uration = ", "│ 1;"]
+        );
+    }
+}
@@ -0,0 +1,153 @@
+mod changelog;
+mod markdown;
+
+pub use changelog::{Changes, changes_since};
+pub use markdown::render;
+
+use crate::prelude::*;
+use colored::Colorize;
+use std::path::PathBuf;
+use thunderstore::VersionIdent;
+
+/// The markdown documents a package version can be published with
+#[derive(Clone, Copy)]
+pub enum Document {
+    Readme,
+    Changelog,
+}
+
+impl Document {
+    fn file_name(self) -> &'static str {
+        match self {
+            Self::Readme => "README.md",
+            Self::Changelog => "CHANGELOG.md",
+        }
+    }
+}
+
+impl std::fmt::Display for Document {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Readme => write!(f, "README"),
+            Self::Changelog => write!(f, "changelog"),
+        }
+    }
+}
+
+/// Where a document of a package version is cached once it has been fetched
+fn cache_path(ident: &VersionIdent, document: Document) -> PathBuf {
+    CACHE_DIR
+        .join("docs")
+        .join(format!("{}-{}", ident, document.file_name()))
+}
+
+/// Reads a document out of the version's archive, if the archive has already been downloaded
+fn read_from_archive(ident: &VersionIdent, document: Document) -> anyhow::Result<Option<String>> {
+    let path = crate::install::archive_path(ident);
+    if !std::fs::exists(&path)? {
+        return Ok(None);
+    }
+
+    let files = crate::install::read_archive(&std::fs::read(path)?)?;
+    Ok(files
+        .into_iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(document.file_name()))
+        .map(|(_, contents)| String::from_utf8_lossy(&contents).into_owned()))
+}
+
+/// Gets a document of a package version from the cache, the downloaded archive or Thunderstore, in
+/// that order, caching it for next time. `None` if the version wasn't published with one.
+pub async fn fetch(
+    api: &thunderstore::Client,
+    ident: &VersionIdent,
+    document: Document,
+) -> anyhow::Result<Option<String>> {
+    let path = cache_path(ident, document);
+    if std::fs::exists(&path)? {
+        return Ok(Some(std::fs::read_to_string(path)?));
+    }
+
+    let markdown = match read_from_archive(ident, document)? {
+        Some(markdown) => markdown,
+        None => {
+            let fetched = match document {
+                Document::Readme => api.get_readme(ident).await,
+                Document::Changelog => api.get_changelog(ident).await,
+            };
+            match fetched {
+                Ok(markdown) => markdown,
+                Err(thunderstore::Error::NotFound) => return Ok(None),
+                Err(err) => return Err(err.into()),
+            }
+        }
+    };
+
+    std::fs::create_dir_all(CACHE_DIR.join("docs"))?;
+    std::fs::write(path, &markdown)?;
+    Ok(Some(markdown))
+}
+
+enum_select! {
+    /// The ways of moving through a paged document
+    #[derive(Clone, Copy)]
+    enum PagerAction {
+        Next = "Next Page",
+        Previous = "Previous Page",
+        Close = "Close",
+    }
+}
+
+/// Shows rendered markdown a page at a time, with pages sized to fit the terminal
+pub fn page(title: &str, markdown: &str) -> anyhow::Result<()> {
+    let (width, height) = term_size::dimensions().unwrap_or((80, 24));
+    let lines = markdown::render(markdown, width.saturating_sub(1).max(20));
+    // The title, the pager menu and its help message take up the rest of the screen
+    let page_size = height.saturating_sub(6).max(1);
+    let pages = lines.chunks(page_size).collect::<Vec<_>>();
+
+    let mut current = 0;
+    loop {
+        clearscreen::clear()?;
+        println!("{}", title.bold());
+        for line in pages.get(current).copied().unwrap_or_default() {
+            println!("{}", line);
+        }
+
+        let actions = [
+            (current + 1 < pages.len()).then_some(PagerAction::Next),
+            (current > 0).then_some(PagerAction::Previous),
+            Some(PagerAction::Close),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+        let message = format!("Page {} of {}", current + 1, pages.len().max(1));
+        match inquire::Select::new(&message, actions).prompt_skippable()? {
+            Some(PagerAction::Next) => current += 1,
+            Some(PagerAction::Previous) => current -= 1,
+            Some(PagerAction::Close) | None => return Ok(()),
+        }
+    }
+}
+
+/// Fetches a document of a package version and pages through it
+pub async fn view(
+    api: &thunderstore::Client,
+    ident: &VersionIdent,
+    document: Document,
+) -> anyhow::Result<()> {
+    clearscreen::clear()?;
+    println!("Fetching the {} of {}...", document, ident);
+
+    match fetch(api, ident, document).await? {
+        Some(markdown) => page(&format!("{} {}", ident, document), &markdown),
+        None => {
+            println!("{} was not published with a {}", ident, document);
+            inquire::Confirm::new("Press enter to continue")
+                .with_default(true)
+                .prompt_skippable()?;
+            Ok(())
+        }
+    }
+}
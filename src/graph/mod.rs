@@ -0,0 +1,229 @@
+use crate::{ProgramState, prelude::*, utils};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fmt::Write,
+    path::PathBuf,
+};
+
+/// The formats a dependency graph can be exported to
+#[derive(clap::ValueEnum, Clone, Copy)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    Dot,
+    /// A Mermaid flowchart
+    Mermaid,
+    /// Lists of nodes and edges
+    Json,
+}
+
+/// Which set of mods a dependency graph is built from
+#[derive(clap::ValueEnum, Clone, Copy)]
+pub enum GraphSource {
+    /// The mods in the mods directory, using the dependencies from their manifests
+    Installed,
+    /// The requirements resolved against the Thunderstore index, along with their dependencies
+    Required,
+}
+
+#[derive(Serialize)]
+struct Node {
+    namespace: String,
+    name: String,
+    /// `None` when the package is depended on but neither installed nor found in the index
+    version: Option<semver::Version>,
+}
+
+#[derive(Serialize, PartialEq, Eq, PartialOrd, Ord)]
+struct Edge {
+    from: NamespacedPackage,
+    to: NamespacedPackage,
+    min_version: semver::Version,
+}
+
+/// Packages along with the minimum versions they require of each other
+#[derive(Serialize, Default)]
+struct DependencyGraph {
+    #[serde(serialize_with = "serialize_nodes")]
+    nodes: BTreeMap<NamespacedPackage, Option<semver::Version>>,
+    edges: BTreeSet<Edge>,
+}
+
+fn serialize_nodes<S: serde::Serializer>(
+    nodes: &BTreeMap<NamespacedPackage, Option<semver::Version>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(nodes.iter().map(|(namespaced, version)| Node {
+        namespace: namespaced.namespace().to_owned(),
+        name: namespaced.name().to_owned(),
+        version: version.clone(),
+    }))
+}
+
+impl DependencyGraph {
+    fn add_dependency(
+        &mut self,
+        from: &NamespacedPackage,
+        dependency: &thunderstore::VersionIdent,
+    ) {
+        let to = NamespacedPackage::from(dependency);
+        self.nodes.entry(to.clone()).or_insert(None);
+        self.edges.insert(Edge {
+            from: from.clone(),
+            to,
+            min_version: dependency.parsed_version(),
+        });
+    }
+
+    fn from_installed(program_state: &ProgramState) -> anyhow::Result<Self> {
+        let mut graph = Self::default();
+        for installed in utils::get_installed_mods(program_state)? {
+            graph.nodes.insert(
+                installed.namespaced.clone(),
+                Some(installed.metadata.version),
+            );
+            for dependency in &installed.metadata.dependencies {
+                graph.add_dependency(&installed.namespaced, dependency);
+            }
+        }
+        Ok(graph)
+    }
+
+    /// Resolves every requirement to its newest matching version and every dependency to its
+    /// newest version, the same way an update would
+    fn from_required(program_state: &ProgramState) -> Self {
+        let mut graph = Self::default();
+        let mut queue = VecDeque::new();
+
+        for (namespaced, _) in program_state.requirements.iter() {
+            let version = program_state
+                .requirements
+                .get_latest_version(&program_state.packages, namespaced);
+            graph.nodes.insert(
+                namespaced.clone(),
+                version.as_ref().map(|version| version.number.clone()),
+            );
+            queue.extend(version);
+        }
+
+        while let Some(version) = queue.pop_front() {
+            let from = NamespacedPackage::from(&version.ident);
+            for dependency in &version.dependencies {
+                let to = NamespacedPackage::from(dependency);
+                let visited = graph.nodes.get(&to).is_some_and(Option::is_some);
+                graph.add_dependency(&from, dependency);
+                if visited {
+                    continue;
+                }
+
+                let latest = program_state.packages.get(&to).and_then(|package| {
+                    package
+                        .versions
+                        .iter()
+                        .max_by_key(|candidate| candidate.number.clone())
+                });
+                if let Some(latest) = latest {
+                    graph.nodes.insert(to, Some(latest.number.clone()));
+                    queue.push_back(latest.clone());
+                }
+            }
+        }
+
+        graph
+    }
+
+    fn label(namespaced: &NamespacedPackage, version: &Option<semver::Version>) -> String {
+        match version {
+            Some(version) => format!("{} {}", namespaced, version),
+            None => format!("{} (missing)", namespaced),
+        }
+    }
+
+    fn to_dot(&self) -> String {
+        let mut output = String::from("digraph mods {\n    rankdir=LR;\n");
+        for (namespaced, version) in &self.nodes {
+            let _ = writeln!(
+                output,
+                "    \"{}\" [label=\"{}\"];",
+                namespaced,
+                Self::label(namespaced, version)
+            );
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                output,
+                "    \"{}\" -> \"{}\" [label=\">= {}\"];",
+                edge.from, edge.to, edge.min_version
+            );
+        }
+        output.push_str("}\n");
+        output
+    }
+
+    fn to_mermaid(&self) -> String {
+        // Mermaid ids can't contain '/', so nodes are referred to by their position instead
+        let ids = self
+            .nodes
+            .keys()
+            .enumerate()
+            .map(|(index, namespaced)| (namespaced, format!("n{}", index)))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut output = String::from("graph LR\n");
+        for (namespaced, version) in &self.nodes {
+            let _ = writeln!(
+                output,
+                "    {}[\"{}\"]",
+                ids[namespaced],
+                Self::label(namespaced, version)
+            );
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                output,
+                "    {} -->|\">= {}\"| {}",
+                ids[&edge.from], edge.min_version, ids[&edge.to]
+            );
+        }
+        output
+    }
+}
+
+/// Writes the dependency graph of the installed or required mods to a file, or to stdout when no
+/// file is given
+pub async fn perform_export(
+    mut program_state: ProgramState,
+    api: &thunderstore::Client,
+    source: GraphSource,
+    format: GraphFormat,
+    output: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let graph = match source {
+        GraphSource::Installed => DependencyGraph::from_installed(&program_state)?,
+        GraphSource::Required => {
+            if program_state.packages.is_empty() {
+                // Progress would end up in the graph when it is written to stdout
+                if output.is_some() {
+                    println!("Refreshing packages...");
+                }
+                program_state.refresh_packages(api).await?;
+            }
+            DependencyGraph::from_required(&program_state)
+        }
+    };
+
+    let exported = match format {
+        GraphFormat::Dot => graph.to_dot(),
+        GraphFormat::Mermaid => graph.to_mermaid(),
+        GraphFormat::Json => serde_json::to_string_pretty(&graph)? + "\n",
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, exported)?;
+            println!("Wrote the dependency graph to {}", path.display());
+        }
+        None => print!("{}", exported),
+    }
+    Ok(())
+}
@@ -0,0 +1,208 @@
+use crate::{ProgramState, prelude::*, utils::InstalledMod};
+use anyhow::anyhow;
+use chrono::{DateTime, Months, Utc};
+use colored::Colorize;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Something wrong with an installed mod, or with a package it depends on
+#[derive(Debug)]
+pub enum Problem {
+    Removed,
+    Deprecated,
+    /// The package has not been updated since the given date
+    Stale(DateTime<Utc>),
+    /// Packages the mod depends on, directly or indirectly, which have problems of their own
+    Dependencies(Vec<(NamespacedPackage, Problem)>),
+}
+
+impl Problem {
+    /// A short label for the status column of the installed mods
+    pub fn status(&self) -> &'static str {
+        match self {
+            Self::Removed => "Removed",
+            Self::Deprecated => "Deprecated",
+            Self::Stale(_) => "Not Updated",
+            Self::Dependencies(_) => "Bad Dependency",
+        }
+    }
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Removed => write!(f, "is no longer on Thunderstore"),
+            Self::Deprecated => write!(f, "is deprecated"),
+            Self::Stale(updated) => {
+                write!(
+                    f,
+                    "has not been updated since {}",
+                    updated.format("%Y-%m-%d")
+                )
+            }
+            Self::Dependencies(dependencies) => {
+                let dependencies = dependencies
+                    .iter()
+                    .map(|(namespaced, problem)| format!("{}, which {}", namespaced, problem))
+                    .collect::<Vec<_>>();
+                write!(f, "depends on {}", dependencies.join("; "))
+            }
+        }
+    }
+}
+
+/// Checks installed mods against the cached Thunderstore index
+pub struct HealthCheck<'a> {
+    program_state: &'a ProgramState,
+    installed: HashMap<&'a NamespacedPackage, &'a InstalledMod>,
+    /// Packages last updated before this are flagged, `None` if they never are
+    stale_before: Option<DateTime<Utc>>,
+}
+
+impl<'a> HealthCheck<'a> {
+    pub fn new(
+        program_state: &'a ProgramState,
+        installed: &'a [InstalledMod],
+        stale_after_months: u32,
+    ) -> Self {
+        Self {
+            program_state,
+            installed: installed
+                .iter()
+                .map(|installed| (&installed.namespaced, installed))
+                .collect(),
+            stale_before: (stale_after_months > 0)
+                .then(|| Utc::now().checked_sub_months(Months::new(stale_after_months)))
+                .flatten(),
+        }
+    }
+
+    /// The problem with a package itself, ignoring its dependencies. A version which has been
+    /// deactivated counts as deprecated.
+    ///
+    /// Mods installed by hand may never have been on Thunderstore, so a package missing from the
+    /// index only counts as removed when this program installed it or another package depends on
+    /// it.
+    fn own_problem(&self, namespaced: &NamespacedPackage, depended_on: bool) -> Option<Problem> {
+        let Some(package) = self.program_state.packages.get(namespaced) else {
+            let published = depended_on || self.program_state.lockfile.get(namespaced).is_some();
+            return published.then_some(Problem::Removed);
+        };
+
+        let deactivated = self.installed.get(namespaced).is_some_and(|installed| {
+            package
+                .versions
+                .iter()
+                .any(|version| version.number == installed.metadata.version && !version.is_active)
+        });
+        if package.is_deprecated || deactivated {
+            return Some(Problem::Deprecated);
+        }
+
+        self.stale_before
+            .filter(|stale_before| package.date_updated < *stale_before)
+            .map(|_| Problem::Stale(package.date_updated))
+    }
+
+    /// The packages a package depends on, as installed or otherwise as its latest version on
+    /// Thunderstore
+    fn dependencies(&self, namespaced: &NamespacedPackage) -> Vec<NamespacedPackage> {
+        if let Some(installed) = self.installed.get(namespaced) {
+            return installed
+                .metadata
+                .dependencies
+                .iter()
+                .map(NamespacedPackage::from)
+                .collect();
+        }
+
+        self.program_state
+            .packages
+            .get(namespaced)
+            .and_then(|package| {
+                package
+                    .versions
+                    .iter()
+                    .max_by_key(|version| &version.number)
+            })
+            .map(|latest| {
+                latest
+                    .dependencies
+                    .iter()
+                    .map(NamespacedPackage::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Every problem with an installed mod, followed by the problems of the packages it depends
+    /// on
+    pub fn problems(&self, installed: &InstalledMod) -> Vec<Problem> {
+        let mut problems = self
+            .own_problem(&installed.namespaced, false)
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        let mut unhealthy = Vec::new();
+        let mut seen = HashSet::from([installed.namespaced.clone()]);
+        let mut queue = VecDeque::from(self.dependencies(&installed.namespaced));
+        while let Some(dependency) = queue.pop_front() {
+            if !seen.insert(dependency.clone()) {
+                continue;
+            }
+            if let Some(problem) = self.own_problem(&dependency, true) {
+                unhealthy.push((dependency.clone(), problem));
+            }
+            queue.extend(self.dependencies(&dependency));
+        }
+
+        if !unhealthy.is_empty() {
+            problems.push(Problem::Dependencies(unhealthy));
+        }
+        problems
+    }
+}
+
+/// Reports every installed mod with problems, failing if there were any
+pub async fn perform_health(
+    mut program_state: ProgramState,
+    api: &thunderstore::Client,
+    update_cache: bool,
+    stale_after_months: Option<u32>,
+) -> anyhow::Result<()> {
+    if update_cache || program_state.packages.is_empty() {
+        println!("Refreshing packages...");
+        program_state.refresh_packages(api).await?;
+    }
+
+    let mut installed = crate::utils::get_installed_mods(&program_state)?;
+    installed.sort_by(|a, b| a.namespaced.cmp(&b.namespaced));
+    let health = HealthCheck::new(
+        &program_state,
+        &installed,
+        stale_after_months.unwrap_or(program_state.settings.stale_after_months),
+    );
+
+    let mut unhealthy = 0;
+    for installed in &installed {
+        let problems = health.problems(installed);
+        if problems.is_empty() {
+            continue;
+        }
+        unhealthy += 1;
+        for problem in problems {
+            println!(
+                "{} {} {} {}",
+                "error:".red(),
+                installed.namespaced,
+                installed.metadata.version,
+                problem
+            );
+        }
+    }
+
+    if unhealthy == 0 {
+        println!("{}", "All installed mods are healthy".green());
+        return Ok(());
+    }
+    Err(anyhow!("{} installed mod(s) have problems", unhealthy))
+}
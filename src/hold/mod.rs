@@ -0,0 +1,84 @@
+use crate::{ProgramState, prelude::NamespacedPackage};
+use anyhow::anyhow;
+use colored::Colorize;
+use pad::{Alignment, PadStr};
+
+/// Holds a mod at its installed version. Mods which are not yet in the requirements are added,
+/// pinned to exactly the version which is installed.
+pub fn perform_hold(
+    program_state: &mut ProgramState,
+    mod_name: NamespacedPackage,
+    reason: Option<String>,
+) -> anyhow::Result<()> {
+    if program_state.requirements.get(&mod_name).is_none() {
+        let locked = program_state
+            .lockfile
+            .get(&mod_name)
+            .ok_or(anyhow!("{} is not installed", mod_name))?;
+
+        let pinned = semver::VersionReq::parse(&format!("={}", locked.version))?;
+        program_state.requirements.insert(mod_name.clone(), pinned);
+    }
+
+    let requirement = program_state
+        .requirements
+        .get_mut(&mod_name)
+        .ok_or(anyhow!("{} is not in the requirements file", mod_name))?;
+    requirement.held = true;
+    requirement.hold_reason = reason;
+
+    program_state.save_requirements()?;
+    println!("Holding {}", mod_name);
+    Ok(())
+}
+
+/// Removes the hold from a mod, allowing it to be updated again
+pub fn perform_release(
+    program_state: &mut ProgramState,
+    mod_name: &NamespacedPackage,
+) -> anyhow::Result<()> {
+    let requirement = program_state
+        .requirements
+        .get_mut(mod_name)
+        .filter(|requirement| requirement.held)
+        .ok_or(anyhow!("{} is not being held", mod_name))?;
+    requirement.held = false;
+    requirement.hold_reason = None;
+
+    program_state.save_requirements()?;
+    println!("Released {}", mod_name);
+    Ok(())
+}
+
+/// Prints every held mod along with its installed version and the reason it is held
+pub fn list_holds(program_state: &ProgramState) {
+    let mut holds = program_state
+        .requirements
+        .iter()
+        .filter(|(_, requirement)| requirement.held)
+        .collect::<Vec<_>>();
+    holds.sort_by_key(|(namespaced, _)| *namespaced);
+
+    if holds.is_empty() {
+        println!("No mods are being held");
+        return;
+    }
+
+    for (namespaced, requirement) in holds {
+        let installed = program_state
+            .lockfile
+            .get(namespaced)
+            .map(|locked| locked.version.to_string())
+            .unwrap_or("not installed".into());
+
+        println!(
+            "{}{}{}",
+            namespaced
+                .to_string()
+                .pad_to_width_with_alignment(40, Alignment::Left)
+                .yellow(),
+            installed.pad_to_width_with_alignment(16, Alignment::Left),
+            requirement.hold_reason.as_deref().unwrap_or("-")
+        );
+    }
+}
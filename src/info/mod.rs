@@ -0,0 +1,136 @@
+use crate::{ProgramState, prelude::*};
+use anyhow::anyhow;
+use colored::Colorize;
+use thunderstore::models::PackageVersionV1;
+
+/// Formats a number of bytes using the largest fitting binary unit
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// The versions of a package, newest first
+pub fn versions_newest_first(package: &SearchablePackage) -> Vec<&PackageVersionV1> {
+    let mut versions = package.versions.iter().collect::<Vec<_>>();
+    versions.sort_by(|a, b| b.number.cmp(&a.number));
+    versions
+}
+
+/// Prints the metadata of a package which isn't specific to any of its versions
+pub fn print_metadata(package: &SearchablePackage) {
+    let mut categories = package.categories.iter().cloned().collect::<Vec<_>>();
+    categories.sort();
+    let yes_no = |value: bool| if value { "yes" } else { "no" };
+
+    println!("Owner: {}", package.namespace);
+    println!("Package URL: {}", package.package_url);
+    if let Some(website) = versions_newest_first(package)
+        .first()
+        .map(|latest| latest.website_url.as_str())
+        .filter(|website| !website.is_empty())
+    {
+        println!("Website: {}", website);
+    }
+    if let Some(donation_link) = &package.donation_link {
+        println!("Donations: {}", donation_link);
+    }
+    println!(
+        "Categories: {}",
+        if categories.is_empty() {
+            "None".to_owned()
+        } else {
+            categories.join(", ")
+        }
+    );
+    println!("Pinned: {}", yes_no(package.is_pinned));
+    if package.is_deprecated {
+        println!("Deprecated: {}", "yes".red());
+    } else {
+        println!("Deprecated: no");
+    }
+    if package.has_nsfw_content {
+        println!("NSFW: {}", "yes".yellow());
+    }
+    println!("Created: {}", package.date_created.format("%Y-%m-%d"));
+    println!("Updated: {}", package.date_updated.format("%Y-%m-%d"));
+    println!(
+        "Downloads: {}, Rating: {}",
+        package.total_downloads(),
+        package.rating_score
+    );
+}
+
+/// Prints the versions of a package with their publish date, downloads, size and dependencies,
+/// newest first. With a `limit`, only that many are listed and the rest are counted.
+pub fn print_version_history(package: &SearchablePackage, limit: Option<usize>) {
+    println!("--- Versions ---");
+    let versions = versions_newest_first(package);
+    let limit = limit.unwrap_or(versions.len());
+    for version in versions.iter().take(limit) {
+        println!(
+            "{}  {}  {} downloads  {}{}",
+            version.number.to_string().cyan(),
+            version.date_created.format("%Y-%m-%d"),
+            version.downloads,
+            format_size(version.file_size),
+            if version.is_active {
+                "".normal()
+            } else {
+                "  [inactive]".red()
+            }
+        );
+        for dependency in &version.dependencies {
+            println!(
+                "    {} >= {}",
+                NamespacedPackage::from(dependency),
+                dependency.version()
+            );
+        }
+    }
+    if versions.len() > limit {
+        println!("...and {} older versions", versions.len() - limit);
+    }
+}
+
+/// Prints the full metadata and version history of a package
+pub async fn perform_info(
+    mut program_state: ProgramState,
+    api: &thunderstore::Client,
+    target: &NamespacedPackage,
+) -> anyhow::Result<()> {
+    if program_state.packages.is_empty() {
+        println!("Refreshing packages...");
+        program_state.refresh_packages(api).await?;
+    }
+
+    let package = program_state
+        .packages
+        .get(target)
+        .ok_or(anyhow!("{} was not found on Thunderstore", target))?;
+
+    println!("{}", target.to_string().bold());
+    if let Some(latest) = versions_newest_first(package).first() {
+        println!("{}", latest.description);
+    }
+    if let Some(locked) = program_state.lockfile.get(target) {
+        println!("Installed: {}", locked.version.to_string().green());
+    }
+    println!();
+    print_metadata(package);
+    println!();
+    print_version_history(package, None);
+
+    Ok(())
+}
@@ -0,0 +1,282 @@
+mod review;
+
+pub use review::review_and_install;
+
+use crate::{ProgramState, prelude::*, utils};
+use anyhow::anyhow;
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    io::{Cursor, Read},
+    path::{Path, PathBuf},
+};
+use thunderstore::{VersionIdent, models::PackageVersionV1};
+
+/// The location of the cached archive for a specific package version
+pub fn archive_path(ident: &VersionIdent) -> PathBuf {
+    ARCHIVE_DIR.join(format!("{}.zip", ident))
+}
+
+/// Gets the zip archive for a package version, downloading and caching it if it has not been
+/// downloaded before
+pub async fn fetch_archive(
+    api: &thunderstore::Client,
+    ident: &VersionIdent,
+) -> anyhow::Result<Vec<u8>> {
+    let path = archive_path(ident);
+
+    if std::fs::exists(&path)? {
+        return Ok(std::fs::read(path)?);
+    }
+
+    let bytes = api.download(ident).await?.to_vec();
+
+    std::fs::create_dir_all(ARCHIVE_DIR.as_path())?;
+    std::fs::write(path, &bytes)?;
+
+    Ok(bytes)
+}
+
+/// Reads every file out of a package archive, keyed by its relative path as stored in the lockfile
+pub fn read_archive(bytes: &[u8]) -> anyhow::Result<BTreeMap<String, Vec<u8>>> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))?;
+    let mut files = BTreeMap::new();
+
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        if file.is_dir() {
+            continue;
+        }
+
+        let relative = file.enclosed_name().ok_or(anyhow!(
+            "Package archive contains an unsafe path: {}",
+            file.name()
+        ))?;
+        let key = utils::relative_key(&relative)?;
+
+        let mut contents = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut contents)?;
+        files.insert(key, contents);
+    }
+
+    Ok(files)
+}
+
+/// Writes a single archive file into a package's install folder
+pub fn write_archive_file(install_dir: &Path, key: &str, contents: &[u8]) -> anyhow::Result<()> {
+    let path = install_dir.join(key);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, contents)?;
+    Ok(())
+}
+
+/// Whether a package version is still installed or kept for rollbacks by the session or any
+/// profile of the managed game. Archives are shared between profiles, so they are only removed
+/// once none of them use it.
+fn archive_in_use(
+    program_state: &ProgramState,
+    namespaced: &NamespacedPackage,
+    version: &semver::Version,
+) -> anyhow::Result<bool> {
+    let uses = |lockfile: &Lockfile| {
+        lockfile
+            .get(namespaced)
+            .is_some_and(|locked| locked.version == *version || locked.previous.contains(version))
+    };
+    if uses(&program_state.lockfile) {
+        return Ok(true);
+    }
+
+    let profiles = crate::profile::Profiles::load(&program_state.managed_game)?;
+    for name in profiles.profiles.keys() {
+        // The session's own lock file on disk is the one being replaced
+        if program_state.project.is_none() && *name == program_state.profile {
+            continue;
+        }
+        let path = ProgramState::lock_path(&program_state.managed_game, name);
+        // An unreadable lock file might still need the archive
+        if Lockfile::read(&path).is_ok_and(|lockfile| !uses(&lockfile)) {
+            continue;
+        }
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+/// Installs a single package version into the mods directory, replacing any previously installed
+/// version and recording the hash of every extracted file in the lockfile. Disabled packages are
+/// updated in place within the disabled mods folder.
+///
+/// The replaced version is added to the package's history, and archives of versions which fall
+/// out of that history are removed from the cache.
+pub async fn install_version(
+    program_state: &mut ProgramState,
+    api: &thunderstore::Client,
+    ident: &VersionIdent,
+) -> anyhow::Result<()> {
+    let namespaced = NamespacedPackage::from(ident);
+    let install_dir = program_state.install_dir(&namespaced);
+    let version = ident.parsed_version();
+
+    let archive = fetch_archive(api, ident).await?;
+    let files = read_archive(&archive)?;
+
+    if std::fs::exists(&install_dir)? {
+        std::fs::remove_dir_all(&install_dir)?;
+    }
+    std::fs::create_dir_all(&install_dir)?;
+
+    let mut hashes = BTreeMap::new();
+    for (key, contents) in &files {
+        write_archive_file(&install_dir, key, contents)?;
+        hashes.insert(key.clone(), utils::hash_bytes(contents));
+    }
+
+    let mut previous = match program_state.lockfile.get(&namespaced) {
+        Some(locked) if locked.version != version => std::iter::once(locked.version.clone())
+            .chain(locked.previous.iter().cloned())
+            .collect(),
+        Some(locked) => locked.previous.clone(),
+        None => Vec::new(),
+    };
+    previous.retain(|previous| *previous != version);
+
+    let kept_versions = program_state.settings.kept_versions;
+    let dropped = previous.split_off(kept_versions.min(previous.len()));
+
+    program_state.lockfile.insert(
+        namespaced.clone(),
+        LockedPackage {
+            version,
+            files: hashes,
+            previous,
+        },
+    );
+    program_state.save_lockfile()?;
+
+    for dropped in dropped {
+        if !archive_in_use(program_state, &namespaced, &dropped)? {
+            let dropped = VersionIdent::new(
+                namespaced.namespace(),
+                namespaced.name(),
+                dropped.to_string(),
+            );
+            let _ = std::fs::remove_file(archive_path(&dropped));
+        }
+    }
+
+    Ok(())
+}
+
+/// Collects the given version along with every dependency which is not already installed at or
+/// above the minimum version requested. Each dependency resolves to its newest published version,
+/// unless it is being held.
+pub fn resolve_install_set(
+    program_state: &ProgramState,
+    version: &PackageVersionV1,
+) -> anyhow::Result<Vec<PackageVersionV1>> {
+    resolve_combined_install_set(program_state, std::slice::from_ref(version))
+}
+
+/// Collects several versions along with the dependencies any of them is missing, each package
+/// appearing once. The given versions come first, in order, followed by their dependencies.
+pub fn resolve_combined_install_set(
+    program_state: &ProgramState,
+    versions: &[PackageVersionV1],
+) -> anyhow::Result<Vec<PackageVersionV1>> {
+    let mut to_install = Vec::new();
+    let mut seen = HashSet::new();
+    for version in versions {
+        if seen.insert(NamespacedPackage::from(&version.ident)) {
+            to_install.push(version.clone());
+        }
+    }
+    let mut queue = to_install
+        .iter()
+        .flat_map(|version| version.dependencies.iter().cloned())
+        .collect::<VecDeque<_>>();
+
+    while let Some(dependency) = queue.pop_front() {
+        let namespaced = NamespacedPackage::from(&dependency);
+        if !seen.insert(namespaced.clone()) {
+            continue;
+        }
+
+        if let Some(locked) = program_state.lockfile.get(&namespaced) {
+            if locked.version >= dependency.parsed_version() {
+                continue;
+            }
+
+            if program_state.requirements.is_held(&namespaced) {
+                println!(
+                    "Keeping held dependency {} at {}, {} was requested",
+                    namespaced,
+                    locked.version,
+                    dependency.version()
+                );
+                continue;
+            }
+        }
+
+        let package = program_state.packages.get(&namespaced).ok_or(anyhow!(
+            "Unable to find dependency {} on Thunderstore",
+            namespaced
+        ))?;
+
+        let latest = package
+            .versions
+            .iter()
+            .max_by_key(|version| version.ident.parsed_version())
+            .ok_or(anyhow!("Dependency {} has no versions", namespaced))?;
+
+        queue.extend(latest.dependencies.iter().cloned());
+        to_install.push(latest.clone());
+    }
+
+    Ok(to_install)
+}
+
+/// Installs a package version and its missing dependencies, adding the package to the
+/// requirements so that it is tracked by future updates. An older version is required exactly, so
+/// that the next update doesn't replace it with the newest version.
+pub async fn install_with_dependencies(
+    program_state: &mut ProgramState,
+    api: &thunderstore::Client,
+    version: &PackageVersionV1,
+) -> anyhow::Result<()> {
+    install_all_with_dependencies(program_state, api, std::slice::from_ref(version)).await
+}
+
+/// Installs several package versions and the dependencies they are missing in one go, adding each
+/// of the versions to the requirements as [`install_with_dependencies`] does
+pub async fn install_all_with_dependencies(
+    program_state: &mut ProgramState,
+    api: &thunderstore::Client,
+    versions: &[PackageVersionV1],
+) -> anyhow::Result<()> {
+    let to_install = resolve_combined_install_set(program_state, versions)?;
+
+    for package_version in &to_install {
+        println!("Installing {}...", package_version.ident);
+        install_version(program_state, api, &package_version.ident).await?;
+    }
+
+    for version in versions {
+        let namespaced = NamespacedPackage::from(&version.ident);
+        let is_latest = program_state
+            .packages
+            .get(&namespaced)
+            .and_then(|package| package.versions.iter().map(|other| &other.number).max())
+            .is_none_or(|latest| *latest <= version.number);
+        let operator = if is_latest { "^" } else { "=" };
+
+        program_state.requirements.insert(
+            namespaced,
+            semver::VersionReq::parse(&format!("{}{}", operator, version.ident.version()))?,
+        );
+    }
+    program_state.save_requirements()?;
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use crate::{ProgramState, prelude::*};
+use colored::Colorize;
+use thunderstore::models::PackageVersionV1;
+
+/// Prints a line of the review for a version which is about to be installed
+fn print_version(program_state: &ProgramState, version: &PackageVersionV1) {
+    let change = match program_state
+        .lockfile
+        .get(&NamespacedPackage::from(&version.ident))
+    {
+        Some(locked) if locked.version == version.ident.parsed_version() => {
+            " (reinstall)".dimmed().to_string()
+        }
+        Some(locked) => format!(" (from {})", locked.version).yellow().to_string(),
+        None => String::new(),
+    };
+    println!(
+        "  {} {}{}",
+        version.ident,
+        crate::info::format_size(version.file_size).dimmed(),
+        change
+    );
+}
+
+/// Shows the packages which are about to be installed, together with the dependencies they pull
+/// in and the size of the download, and installs all of them once the user confirms
+pub async fn review_and_install(
+    program_state: &mut ProgramState,
+    api: &thunderstore::Client,
+    versions: &[PackageVersionV1],
+) -> anyhow::Result<()> {
+    let to_install = crate::install::resolve_combined_install_set(program_state, versions)?;
+    let (selected, dependencies) = to_install.split_at(versions.len().min(to_install.len()));
+
+    clearscreen::clear()?;
+    println!("{}", "Selected packages".bold());
+    for version in selected {
+        print_version(program_state, version);
+    }
+    if !dependencies.is_empty() {
+        println!("{}", "Dependencies".bold());
+        for version in dependencies {
+            print_version(program_state, version);
+        }
+    }
+
+    let (cached, to_download): (Vec<_>, Vec<_>) = to_install
+        .iter()
+        .partition(|version| super::archive_path(&version.ident).exists());
+    let size = |versions: &[&PackageVersionV1]| {
+        crate::info::format_size(versions.iter().map(|version| version.file_size).sum())
+    };
+    print!("\nTotal download size: {}", size(&to_download));
+    if !cached.is_empty() {
+        print!(" ({} already downloaded)", size(&cached));
+    }
+    println!();
+
+    let confirmed = inquire::Confirm::new(&format!("Install {} packages?", to_install.len()))
+        .with_default(true)
+        .prompt_skippable()?;
+    if confirmed != Some(true) {
+        return Ok(());
+    }
+
+    println!();
+    crate::install::install_all_with_dependencies(program_state, api, versions).await?;
+
+    inquire::Confirm::new("Installation complete. Press enter to continue")
+        .with_default(true)
+        .prompt_skippable()?;
+
+    Ok(())
+}
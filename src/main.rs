@@ -1,10 +1,30 @@
+mod author;
 mod back_dialog;
+mod check;
 mod clean;
+mod dependents;
+mod docs;
+mod graph;
+mod health;
+mod hold;
+mod info;
+mod install;
 mod main_menu;
 pub mod prelude;
+mod profile;
+mod project;
+mod requirements;
+mod rollback;
+mod search;
+mod settings;
+mod snapshot;
+mod toggle;
 mod uninstall;
 mod update;
 pub mod utils;
+mod verify;
+mod whats_new;
+mod why;
 
 use anyhow::anyhow;
 use chrono::{DateTime, Local};
@@ -12,8 +32,9 @@ use clap::{Parser, Subcommand};
 use prelude::*;
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufReader, BufWriter};
+use std::io::{BufReader, BufWriter, IsTerminal};
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 /// Global state for this program's session
 pub struct ProgramState {
@@ -21,12 +42,23 @@ pub struct ProgramState {
     mods_dir: PathBuf,
     /// Which game this session is managing
     managed_game: String,
+    /// The profile of the managed game this session is working with
+    profile: String,
+    /// Settings of the active profile
+    settings: profile::ProfileSettings,
+    /// The `thundermods.toml` this session was loaded from. When set, it takes the place of the
+    /// profile's requirements and settings
+    project: Option<PathBuf>,
     /// cached packages from Thunderstore
     packages: HashMap<NamespacedPackage, SearchablePackage>,
     /// The mod requirements for this session
     requirements: Requirements,
+    /// The packages installed by this program along with their original file hashes
+    lockfile: Lockfile,
     /// The last time the package cache was updated
     last_updated: Option<DateTime<Local>>,
+    /// The packages depending on each package, built from `packages` the first time it is needed
+    reverse_dependencies: OnceLock<dependents::ReverseDependencies>,
 }
 
 impl ProgramState {
@@ -56,8 +88,50 @@ impl ProgramState {
         None
     }
 
-    fn config_path(managed_game: &str) -> PathBuf {
-        CONFIG_DIR.join(format!("requirements_{}.json", managed_game))
+    /// The requirements file of a profile. It may be written as JSON, TOML or YAML, and defaults
+    /// to JSON when it doesn't exist yet.
+    fn config_path(managed_game: &str, profile: &str) -> PathBuf {
+        if profile == profile::DEFAULT_PROFILE {
+            return requirements::find(&CONFIG_DIR, &format!("requirements_{}", managed_game));
+        }
+        requirements::find(&profile::profile_dir(managed_game, profile), "requirements")
+    }
+
+    fn lock_path(managed_game: &str, profile: &str) -> PathBuf {
+        if profile == profile::DEFAULT_PROFILE {
+            return CONFIG_DIR.join(format!("lock_{}.json", managed_game));
+        }
+        profile::profile_dir(managed_game, profile).join("lock.json")
+    }
+
+    /// The folder disabled mods are moved to. It sits next to the mods directory so that the mod
+    /// loader never picks it up.
+    fn disabled_dir(&self) -> PathBuf {
+        let mut dir_name = self.mods_dir.file_name().unwrap_or_default().to_owned();
+        dir_name.push("_disabled");
+        self.mods_dir.with_file_name(dir_name)
+    }
+
+    /// The BepInEx config folder, which sits next to the plugins folder the mods are deployed to.
+    /// `None` unless the mods directory is inside a `BepInEx` folder, as the folder next to it
+    /// could hold anything else.
+    fn game_config_dir(&self) -> Option<PathBuf> {
+        let parent = self.mods_dir.parent()?;
+        parent
+            .file_name()?
+            .to_str()?
+            .eq_ignore_ascii_case("BepInEx")
+            .then(|| parent.join("config"))
+    }
+
+    /// The folder a package is currently installed to, taking into account whether it has been
+    /// disabled.
+    fn install_dir(&self, namespaced: &NamespacedPackage) -> PathBuf {
+        let disabled = self.disabled_dir().join(namespaced.dir_name());
+        if disabled.exists() {
+            return disabled;
+        }
+        self.mods_dir.join(namespaced.dir_name())
     }
 
     fn get_last_updated_from_path(path: &Path) -> Option<DateTime<Local>> {
@@ -74,9 +148,22 @@ impl ProgramState {
         Some(DateTime::from_timestamp(timestamp, 0)?.with_timezone(&Local))
     }
 
-    /// Attempts to pull thunderstore mod data from the cache if it exists.
-    fn from_cache(args: ProgramArgs) -> Self {
-        let cache_file_name = Self::cache_path(&args.managed_game);
+    /// Attempts to pull thunderstore mod data from the cache if it exists, and loads the selected
+    /// profile.
+    fn from_cache(args: ProgramArgs) -> anyhow::Result<Self> {
+        Self::load(args, true)
+    }
+
+    /// Loads the cached index along with the selected profile's requirements and lock file, for
+    /// commands which never touch the mods directory. A mods directory doesn't have to be
+    /// configured, as is usually the case in CI.
+    fn index_only(args: ProgramArgs) -> anyhow::Result<Self> {
+        Self::load(args, false)
+    }
+
+    fn load(args: ProgramArgs, require_mods_dir: bool) -> anyhow::Result<Self> {
+        let (managed_game, project) = args.resolve_game()?;
+        let cache_file_name = Self::cache_path(&managed_game);
         let packages = cache_file_name
             .clone()
             .and_then(|path| File::open(path).ok())
@@ -98,18 +185,91 @@ impl ProgramState {
             })
             .unwrap_or_default();
 
-        let requirements: Option<Requirements> = File::open(Self::config_path(&args.managed_game))
-            .ok()
-            .map(BufReader::new)
-            .and_then(|reader| serde_json::from_reader(reader).ok());
-
-        Self {
-            mods_dir: args.mods_dir,
-            managed_game: args.managed_game,
+        let mut state = Self {
+            mods_dir: PathBuf::new(),
+            managed_game,
+            profile: String::new(),
+            settings: Default::default(),
+            project: None,
             packages,
-            requirements: requirements.unwrap_or_default(),
+            requirements: Default::default(),
+            lockfile: Default::default(),
             last_updated: cache_file_name.and_then(|path| Self::get_last_updated_from_path(&path)),
+            reverse_dependencies: OnceLock::new(),
+        };
+
+        if let Some(project) = project {
+            state.load_project(project, args.mods_dir, require_mods_dir)?;
+            return Ok(state);
         }
+
+        let profiles = profile::Profiles::load(&state.managed_game)?;
+        let profile = args.profile.unwrap_or(profiles.active.clone());
+        state.load_profile(&profiles, &profile, args.mods_dir, require_mods_dir)?;
+
+        Ok(state)
+    }
+
+    /// Switches this session to a project manifest, using its mods directory, requirements and
+    /// options in place of a profile's.
+    fn load_project(
+        &mut self,
+        project: project::Project,
+        mods_dir: Option<PathBuf>,
+        require_mods_dir: bool,
+    ) -> anyhow::Result<()> {
+        let mods_dir = mods_dir
+            .or(project.mods_dir())
+            .or(settings::GlobalSettings::load()?.mods_dir_for(&self.managed_game))
+            .or((!require_mods_dir).then(PathBuf::new))
+            .ok_or(anyhow!(
+                "No mods directory was provided and {} does not have one configured",
+                project.path.display()
+            ))?;
+
+        let lockfile = Lockfile::read(&project.lock_path())?;
+
+        self.mods_dir = mods_dir;
+        self.profile = project::PROJECT_PROFILE.to_owned();
+        self.settings = project.manifest.options;
+        self.requirements = project.manifest.requirements;
+        self.lockfile = lockfile;
+        self.project = Some(project.path);
+        Ok(())
+    }
+
+    /// Switches this session to a profile, loading its mods directory, requirements and lock file.
+    /// `mods_dir` overrides the directory configured for the profile, which in turn overrides the
+    /// default mods directory from the global settings. Without `require_mods_dir`, the profile is
+    /// loaded even when none of them is set.
+    fn load_profile(
+        &mut self,
+        profiles: &profile::Profiles,
+        name: &str,
+        mods_dir: Option<PathBuf>,
+        require_mods_dir: bool,
+    ) -> anyhow::Result<()> {
+        let profile = profiles.get(name)?;
+        let mods_dir = mods_dir
+            .or(profile.mods_dir.clone())
+            .or(settings::GlobalSettings::load()?.mods_dir_for(&self.managed_game))
+            .or((!require_mods_dir).then(PathBuf::new))
+            .ok_or(anyhow!(
+                "No mods directory was provided and the {} profile does not have one configured",
+                name
+            ))?;
+
+        let requirements = requirements::read(&Self::config_path(&self.managed_game, name))?;
+
+        let lockfile = Lockfile::read(&Self::lock_path(&self.managed_game, name))?;
+
+        self.mods_dir = mods_dir;
+        self.profile = name.to_owned();
+        self.settings = profile.settings.clone();
+        self.project = None;
+        self.requirements = requirements;
+        self.lockfile = lockfile;
+        Ok(())
     }
 
     /// Attempts to save the program state to cache
@@ -145,6 +305,49 @@ impl ProgramState {
         Ok(())
     }
 
+    /// The requirements file of the active profile, or the project manifest
+    fn requirements_path(&self) -> PathBuf {
+        match &self.project {
+            Some(manifest_path) => manifest_path.clone(),
+            None => Self::config_path(&self.managed_game, &self.profile),
+        }
+    }
+
+    /// The lock file of the active profile or project
+    fn lockfile_path(&self) -> PathBuf {
+        match &self.project {
+            Some(manifest_path) => project::lock_path(manifest_path),
+            None => Self::lock_path(&self.managed_game, &self.profile),
+        }
+    }
+
+    /// Saves the current requirements to the active profile's config file, or to the project
+    /// manifest
+    fn save_requirements(&self) -> anyhow::Result<()> {
+        if let Some(manifest_path) = &self.project {
+            return project::save_requirements(manifest_path, &self.requirements);
+        }
+
+        requirements::write(&self.requirements_path(), &self.requirements)
+    }
+
+    /// Saves the current lockfile to the active profile's lock file
+    fn save_lockfile(&self) -> anyhow::Result<()> {
+        let path = self.lockfile_path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(writer, &self.lockfile)?;
+        Ok(())
+    }
+
+    /// The packages depending on each package in the cached index
+    fn reverse_dependencies(&self) -> &dependents::ReverseDependencies {
+        self.reverse_dependencies
+            .get_or_init(|| dependents::ReverseDependencies::build(&self.packages))
+    }
+
     async fn refresh_packages(&mut self, api: &thunderstore::Client) -> anyhow::Result<()> {
         let packages: Vec<SearchablePackage> = api
             .list_packages_v1(&self.managed_game)
@@ -158,11 +361,18 @@ impl ProgramState {
             map.insert(NamespacedPackage::from(&pkg), pkg);
         }
 
-        self.packages = map;
+        let previous = std::mem::replace(&mut self.packages, map);
+        self.reverse_dependencies = OnceLock::new();
         let last_updated = Local::now();
 
-        self.last_updated = Some(last_updated);
+        let since = self.last_updated.replace(last_updated);
         self.cache()?;
+
+        // The very first fetch has nothing to compare against
+        if !previous.is_empty() {
+            whats_new::WhatsNew::between(&previous, &self.packages, since, last_updated)
+                .record(&self.managed_game)?;
+        }
         Ok(())
     }
 }
@@ -172,16 +382,52 @@ impl ProgramState {
 #[derive(Parser)]
 #[command(version, about)]
 struct ProgramArgs {
-    /// The directory where your mods should be deployed
+    /// The directory where your mods should be deployed. Overrides the mods directory configured
+    /// for the selected profile and the configured default mods directory
     #[arg(short = 'd', long, env)]
-    mods_dir: PathBuf,
-    /// The game to be managed. This should match exactly with what is in the Thunderstore website
+    mods_dir: Option<PathBuf>,
+    /// The game to be managed. This should match exactly with what is in the Thunderstore website.
+    /// If not provided, the game of the project manifest is used, then the configured default game
     #[arg(short = 'g', long, env)]
-    managed_game: String,
+    managed_game: Option<String>,
+    /// The profile to work with. If not provided, the project manifest found in the current
+    /// directory or its parents is used, falling back to the active profile of the managed game
+    #[arg(short = 'p', long, env = "THUNDERMODS_PROFILE")]
+    profile: Option<String>,
     #[command(subcommand)]
     command: Option<Commands>,
 }
 
+impl ProgramArgs {
+    /// Works out which game is being managed, along with the project manifest to use in place of
+    /// a profile. A manifest is only used when no profile was asked for and it is for the same
+    /// game as `--managed-game`, though its game is still used when no game was given. The default
+    /// game from the global settings is used last.
+    fn resolve_game(&self) -> anyhow::Result<(String, Option<project::Project>)> {
+        let project = project::Project::find()?.filter(|project| {
+            self.managed_game
+                .as_ref()
+                .is_none_or(|game| *game == project.manifest.game)
+        });
+
+        let managed_game = self
+            .managed_game
+            .clone()
+            .or(project
+                .as_ref()
+                .map(|project| project.manifest.game.clone()))
+            .or(settings::GlobalSettings::load()?.default_game)
+            .ok_or(anyhow!(
+                "No game was provided. Use --managed-game, add a {} to this directory or set a \
+                 default with `config set default_game`",
+                project::MANIFEST_NAME
+            ))?;
+
+        let project = project.filter(|_| self.profile.is_none());
+        Ok((managed_game, project))
+    }
+}
+
 #[derive(Subcommand, Clone)]
 enum Commands {
     /// Update mods for a specified game
@@ -194,6 +440,10 @@ enum Commands {
         /// mods would be updated and which version it would be upated to
         #[arg(long)]
         dry_run: bool,
+        /// Along with --dry-run, print the changelog entries between the installed version of
+        /// each mod and the version it would be updated to
+        #[arg(long, requires = "dry_run")]
+        changelog: bool,
         /// Which mod should be updated. If not provided, all mods will be updated according to the
         /// requirements set in the config file
         #[arg(short = 'm', long, value_parser = NamespacedPackage::value_parser)]
@@ -208,6 +458,144 @@ enum Commands {
         #[arg(short = 'm', long, value_parser = NamespacedPackage::value_parser)]
         mod_name: NamespacedPackage,
     },
+    /// Enables a previously disabled mod so that it is loaded by the game again
+    Enable {
+        /// The name of a mod with the namespace as a prefix followed by a '/'. Ex.
+        /// ModAuthor/ModName
+        #[arg(short = 'm', long, value_parser = NamespacedPackage::value_parser)]
+        mod_name: NamespacedPackage,
+    },
+    /// Disables a mod without uninstalling it by moving it out of the mods directory
+    Disable {
+        /// The name of a mod with the namespace as a prefix followed by a '/'. Ex.
+        /// ModAuthor/ModName
+        #[arg(short = 'm', long, value_parser = NamespacedPackage::value_parser)]
+        mod_name: NamespacedPackage,
+    },
+    /// Holds mods at their installed version so that they are skipped by updates
+    Hold {
+        #[command(subcommand)]
+        action: HoldAction,
+    },
+    /// Restores mods to the version they had before they were last updated
+    Rollback {
+        /// Which mod should be rolled back. If not provided, every mod changed by the last update
+        /// will be rolled back
+        #[arg(short = 'm', long, value_parser = NamespacedPackage::value_parser)]
+        mod_name: Option<NamespacedPackage>,
+    },
+    /// Saves and restores the full state of the mods directory and game config
+    Snapshot {
+        #[command(subcommand)]
+        action: SnapshotAction,
+    },
+    /// Checks every requirement against the Thunderstore index, exiting with an error if any
+    /// requirement is missing, unsatisfiable, deprecated or conflicts with a dependency
+    Check {
+        /// Flag which tells the program to update the Thunderstore mod cache before checking
+        #[arg(short = 'c', long)]
+        update_cache: bool,
+    },
+    /// Checks the installed mods for packages which are deprecated, removed from Thunderstore or
+    /// no longer updated, or which depend on such packages, exiting with an error if any are found
+    Health {
+        /// Flag which tells the program to update the Thunderstore mod cache before checking
+        #[arg(short = 'c', long)]
+        update_cache: bool,
+        /// How many months a mod can go without an update before it is flagged. If not provided,
+        /// the setting of the profile is used. 0 turns the check off
+        #[arg(long)]
+        stale_after_months: Option<u32>,
+    },
+    /// Explains why a mod is installed by listing the shortest dependency chain leading to it from
+    /// each of the requirements
+    Why {
+        /// The name of a mod with the namespace as a prefix followed by a '/'. Ex.
+        /// ModAuthor/ModName
+        #[arg(value_parser = NamespacedPackage::value_parser)]
+        mod_name: NamespacedPackage,
+    },
+    /// Lists the packages published under a namespace, and lets you install any of them
+    Author {
+        /// The namespace the packages are published under. Ex. ModAuthor
+        namespace: String,
+    },
+    /// Lists what changed on Thunderstore since this was last run, across every refresh of the mod
+    /// cache in between: new packages, new versions of installed or required mods, and deprecated
+    /// or removed packages
+    WhatsNew {
+        /// Flag which tells the program to update the Thunderstore mod cache first, including the
+        /// changes since the cache was last updated
+        #[arg(short = 'c', long)]
+        update_cache: bool,
+    },
+    /// Lists every package on Thunderstore which depends on a mod
+    Dependents {
+        /// The name of a mod with the namespace as a prefix followed by a '/'. Ex.
+        /// ModAuthor/ModName
+        #[arg(value_parser = NamespacedPackage::value_parser)]
+        mod_name: NamespacedPackage,
+        /// Only list dependents which are installed on this server
+        #[arg(short = 'i', long)]
+        installed: bool,
+    },
+    /// Shows the metadata and full version history of a package
+    Info {
+        /// The name of a mod with the namespace as a prefix followed by a '/'. Ex.
+        /// ModAuthor/ModName
+        #[arg(value_parser = NamespacedPackage::value_parser)]
+        mod_name: NamespacedPackage,
+    },
+    /// Searches the cached Thunderstore index without any prompts
+    Search {
+        /// Words to search for in package names, authors, descriptions and categories. If not
+        /// provided, every package passing the filters is listed
+        query: Option<String>,
+        #[command(flatten)]
+        filters: search::SearchFilters,
+        /// The order to list packages in. If not provided, the best matches are listed first, or
+        /// the most downloaded packages when there is no query
+        #[arg(short = 's', long, value_enum)]
+        sort: Option<search::SortOrder>,
+        /// The most packages to list
+        #[arg(short = 'n', long)]
+        limit: Option<usize>,
+        /// Print a JSON object per package instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Exports the dependency graph of the installed or required mods
+    Graph {
+        /// The format to export the graph in
+        #[arg(short = 'f', long, value_enum, default_value_t = graph::GraphFormat::Dot)]
+        format: graph::GraphFormat,
+        /// Which mods the graph is built from
+        #[arg(short = 's', long, value_enum, default_value_t = graph::GraphSource::Installed)]
+        source: graph::GraphSource,
+        /// The file to write the graph to. If not provided, the graph is printed
+        #[arg(short = 'o', long)]
+        output: Option<PathBuf>,
+    },
+    /// Checks the files of installed mods against the hashes recorded when they were installed
+    Verify {
+        /// Which mod should be verified. If not provided, all installed mods will be verified
+        #[arg(short = 'm', long, value_parser = NamespacedPackage::value_parser)]
+        mod_name: Option<NamespacedPackage>,
+        /// Restores modified and missing files from the original package archive, and removes
+        /// files which were not part of the package
+        #[arg(long)]
+        repair: bool,
+    },
+    /// Manages named profiles, each with their own mods directory, requirements and settings
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
+    /// Reads and changes the global defaults, such as the default game and mods directory
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
     /// Get the locations for the files the program uses for caching and config
     Files {
         #[command(subcommand)]
@@ -215,56 +603,404 @@ enum Commands {
     },
 }
 
+#[derive(Subcommand, Clone)]
+enum HoldAction {
+    /// Holds a mod at its installed version
+    Add {
+        /// The name of a mod with the namespace as a prefix followed by a '/'. Ex.
+        /// ModAuthor/ModName
+        #[arg(short = 'm', long, value_parser = NamespacedPackage::value_parser)]
+        mod_name: NamespacedPackage,
+        /// Why the mod is being held
+        #[arg(short = 'r', long)]
+        reason: Option<String>,
+    },
+    /// Releases a held mod so that it can be updated again
+    Remove {
+        /// The name of a mod with the namespace as a prefix followed by a '/'. Ex.
+        /// ModAuthor/ModName
+        #[arg(short = 'm', long, value_parser = NamespacedPackage::value_parser)]
+        mod_name: NamespacedPackage,
+    },
+    /// Lists every held mod along with the reason it is held
+    List,
+}
+
+#[derive(Subcommand, Clone)]
+enum SnapshotAction {
+    /// Takes a snapshot of the mods directory, disabled mods, requirements and lock file, along
+    /// with the game config when the mods are deployed to BepInEx/plugins
+    Create {
+        /// A short description of the snapshot
+        #[arg(short = 'l', long)]
+        label: Option<String>,
+    },
+    /// Lists every snapshot taken for the managed game
+    List,
+    /// Shows which files differ between two snapshots, or between a snapshot and the current files
+    Diff {
+        /// The id of the snapshot to compare from
+        from: i64,
+        /// The id of the snapshot to compare to. If not provided, the current files are used
+        to: Option<i64>,
+    },
+    /// Restores the files captured by a snapshot
+    Restore {
+        /// The id of the snapshot to restore
+        id: i64,
+    },
+    /// Deletes a snapshot, along with the stored files no other snapshot uses
+    Delete {
+        /// The id of the snapshot to delete
+        id: i64,
+    },
+    /// Removes the stored files no snapshot uses any more, optionally deleting older snapshots
+    /// first
+    Prune {
+        /// Delete all but this many of the newest snapshots of the profile first
+        #[arg(short = 'k', long)]
+        keep: Option<usize>,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum ProfileAction {
+    /// Creates a new, empty profile
+    Create {
+        /// The name of the new profile
+        name: String,
+        /// The directory where the profile's mods should be deployed
+        #[arg(short = 'd', long)]
+        mods_dir: Option<PathBuf>,
+    },
+    /// Creates a new profile with the same requirements and settings as an existing profile
+    Clone {
+        /// The profile to copy
+        from: String,
+        /// The name of the new profile
+        to: String,
+        /// The directory where the new profile's mods should be deployed
+        #[arg(short = 'd', long)]
+        mods_dir: Option<PathBuf>,
+    },
+    /// Renames a profile
+    Rename {
+        /// The current name of the profile
+        from: String,
+        /// The new name of the profile
+        to: String,
+    },
+    /// Deletes a profile's requirements, lock file and snapshots. Its mods are not removed
+    Delete {
+        /// The profile to delete
+        name: String,
+    },
+    /// Makes a profile the active profile for the managed game
+    Switch {
+        /// The profile to switch to
+        name: String,
+    },
+    /// Lists every profile of the managed game
+    List,
+    /// Shows and changes the settings of the selected profile
+    Settings {
+        /// The directory where the profile's mods should be deployed
+        #[arg(short = 'd', long)]
+        mods_dir: Option<PathBuf>,
+        /// How many previously installed versions of each mod are kept for rollbacks
+        #[arg(long)]
+        kept_versions: Option<usize>,
+        /// Whether a snapshot is taken before every update
+        #[arg(long)]
+        auto_snapshot: Option<bool>,
+        /// How many months a mod can go without an update before the health report flags it. 0
+        /// turns the check off
+        #[arg(long)]
+        stale_after_months: Option<u32>,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum ConfigAction {
+    /// Prints a setting, or every configured setting if no key is given
+    Get {
+        /// The setting to print. One of default_game, mods_dir or games.<game>.mods_dir
+        key: Option<String>,
+    },
+    /// Changes a setting
+    Set {
+        /// The setting to change. One of default_game, mods_dir or games.<game>.mods_dir
+        key: String,
+        /// The new value of the setting
+        value: String,
+    },
+    /// Removes a setting
+    Unset {
+        /// The setting to remove. One of default_game, mods_dir or games.<game>.mods_dir
+        key: String,
+    },
+}
+
 #[derive(Subcommand, Clone)]
 enum FileName {
-    /// File name for the managed game's config file.
+    /// File name for the selected profile's config file.
     Config,
     /// File name for the managed game's Thunderstore cache.
     Cache,
+    /// File name for the selected profile's lock file.
+    Lock,
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let mut args = ProgramArgs::parse();
     let thunderstore_api = thunderstore::Client::new();
-    args.mods_dir = std::path::absolute(args.mods_dir)?;
+    args.mods_dir = args.mods_dir.map(std::path::absolute).transpose()?;
     let command = args.command.clone();
 
+    let needs_game = !matches!(command, Some(Commands::Config { .. }));
+    if needs_game
+        && args.managed_game.is_none()
+        && std::io::stdin().is_terminal()
+        && project::Project::find()?.is_none()
+        && settings::GlobalSettings::load()?.default_game.is_none()
+    {
+        settings::run_wizard(&thunderstore_api).await?;
+    }
+
     if let Some(command) = command {
         use Commands::*;
         match command {
             Update {
                 update_cache,
                 dry_run,
+                changelog,
                 mod_name,
             } => {
-                let program_state = ProgramState::from_cache(args);
-                update::perform_update(program_state, mod_name, update_cache, dry_run).await?;
+                let program_state = ProgramState::from_cache(args)?;
+                update::perform_update(
+                    program_state,
+                    &thunderstore_api,
+                    mod_name,
+                    update_cache,
+                    dry_run,
+                    changelog,
+                )
+                .await?;
             }
             Clean => {}
-            Uninstall { mod_name } => {}
+            Uninstall { mod_name } => {
+                let mut program_state = ProgramState::from_cache(args)?;
+                uninstall::perform_uninstall(&mut program_state, &mod_name)?;
+            }
+            Enable { mod_name } => {
+                let mut program_state = ProgramState::from_cache(args)?;
+                toggle::set_enabled(&mut program_state, &mod_name, true)?;
+            }
+            Disable { mod_name } => {
+                let mut program_state = ProgramState::from_cache(args)?;
+                toggle::set_enabled(&mut program_state, &mod_name, false)?;
+            }
+            Hold { action } => {
+                let mut program_state = ProgramState::from_cache(args)?;
+                match action {
+                    HoldAction::Add { mod_name, reason } => {
+                        hold::perform_hold(&mut program_state, mod_name, reason)?;
+                    }
+                    HoldAction::Remove { mod_name } => {
+                        hold::perform_release(&mut program_state, &mod_name)?;
+                    }
+                    HoldAction::List => hold::list_holds(&program_state),
+                }
+            }
+            Rollback { mod_name } => {
+                let mut program_state = ProgramState::from_cache(args)?;
+                rollback::perform_rollback(&mut program_state, &thunderstore_api, mod_name).await?;
+            }
+            Snapshot { action } => {
+                let program_state = ProgramState::from_cache(args)?;
+                match action {
+                    SnapshotAction::Create { label } => {
+                        snapshot::perform_create(&program_state, label)?;
+                    }
+                    SnapshotAction::List => snapshot::perform_list(&program_state)?,
+                    SnapshotAction::Diff { from, to } => {
+                        snapshot::perform_diff(&program_state, from, to)?;
+                    }
+                    SnapshotAction::Restore { id } => {
+                        snapshot::perform_restore(&program_state, id)?;
+                    }
+                    SnapshotAction::Delete { id } => {
+                        snapshot::perform_delete(&program_state, id)?;
+                    }
+                    SnapshotAction::Prune { keep } => {
+                        snapshot::perform_prune(&program_state, keep)?;
+                    }
+                }
+            }
+            Check { update_cache } => {
+                let program_state = ProgramState::index_only(args)?;
+                check::perform_check(program_state, &thunderstore_api, update_cache).await?;
+            }
+            Health {
+                update_cache,
+                stale_after_months,
+            } => {
+                let program_state = ProgramState::from_cache(args)?;
+                health::perform_health(
+                    program_state,
+                    &thunderstore_api,
+                    update_cache,
+                    stale_after_months,
+                )
+                .await?;
+            }
+            Why { mod_name } => {
+                let program_state = ProgramState::from_cache(args)?;
+                why::perform_why(&program_state, &mod_name)?;
+            }
+            Author { namespace } => {
+                let program_state = ProgramState::from_cache(args)?;
+                author::perform_author(program_state, &thunderstore_api, &namespace).await?;
+            }
+            WhatsNew { update_cache } => {
+                let program_state = ProgramState::from_cache(args)?;
+                whats_new::perform_whats_new(program_state, &thunderstore_api, update_cache)
+                    .await?;
+            }
+            Dependents {
+                mod_name,
+                installed,
+            } => {
+                let program_state = ProgramState::from_cache(args)?;
+                dependents::perform_dependents(
+                    program_state,
+                    &thunderstore_api,
+                    &mod_name,
+                    installed,
+                )
+                .await?;
+            }
+            Info { mod_name } => {
+                let program_state = ProgramState::from_cache(args)?;
+                info::perform_info(program_state, &thunderstore_api, &mod_name).await?;
+            }
+            Search {
+                query,
+                filters,
+                sort,
+                limit,
+                json,
+            } => {
+                // Only hiding installed packages needs to look at the mods directory
+                let program_state = if filters.hide_installed {
+                    ProgramState::from_cache(args)?
+                } else {
+                    ProgramState::index_only(args)?
+                };
+                search::perform_search(
+                    program_state,
+                    &thunderstore_api,
+                    query,
+                    &filters,
+                    sort,
+                    limit,
+                    json,
+                )
+                .await?;
+            }
+            Graph {
+                format,
+                source,
+                output,
+            } => {
+                let program_state = ProgramState::from_cache(args)?;
+                graph::perform_export(program_state, &thunderstore_api, source, format, output)
+                    .await?;
+            }
+            Verify { mod_name, repair } => {
+                let program_state = ProgramState::from_cache(args)?;
+                verify::perform_verify(&program_state, &thunderstore_api, mod_name, repair).await?;
+            }
+            Profile { action } => {
+                let (game, _) = args.resolve_game()?;
+                let game = &game;
+                match action {
+                    ProfileAction::Create { name, mods_dir } => {
+                        profile::perform_create(game, name, mods_dir)?;
+                    }
+                    ProfileAction::Clone { from, to, mods_dir } => {
+                        profile::perform_clone(game, &from, to, mods_dir)?;
+                    }
+                    ProfileAction::Rename { from, to } => {
+                        profile::perform_rename(game, &from, to)?;
+                    }
+                    ProfileAction::Delete { name } => profile::perform_delete(game, &name)?,
+                    ProfileAction::Switch { name } => profile::perform_switch(game, &name)?,
+                    ProfileAction::List => profile::perform_list(game)?,
+                    ProfileAction::Settings {
+                        mods_dir,
+                        kept_versions,
+                        auto_snapshot,
+                        stale_after_months,
+                    } => {
+                        let name = match args.profile {
+                            Some(name) => name,
+                            None => profile::Profiles::load(game)?.active,
+                        };
+                        profile::perform_settings(
+                            game,
+                            &name,
+                            mods_dir,
+                            kept_versions,
+                            auto_snapshot,
+                            stale_after_months,
+                        )?;
+                    }
+                }
+            }
+            Config { action } => match action {
+                ConfigAction::Get { key } => settings::perform_get(key)?,
+                ConfigAction::Set { key, value } => settings::perform_set(&key, Some(value))?,
+                ConfigAction::Unset { key } => settings::perform_set(&key, None)?,
+            },
             Files { file_name } => {
+                let (game, project) = args.resolve_game()?;
+                let profile = match &args.profile {
+                    Some(name) => name.clone(),
+                    None => profile::Profiles::load(&game)?.active,
+                };
                 use FileName::*;
                 match file_name {
                     Cache => {
-                        if let Some(cache_path) = ProgramState::cache_path(&args.managed_game) {
+                        if let Some(cache_path) = ProgramState::cache_path(&game) {
                             println!("{}", cache_path.display())
                         } else {
                             return Err(anyhow!(format!(
                                 "Unable to locate a Thunderstore cache for {}",
-                                args.managed_game
+                                game
                             )));
                         }
                     }
                     Config => {
-                        let config_path = ProgramState::config_path(&args.managed_game);
+                        let config_path = match &project {
+                            Some(project) => project.path.clone(),
+                            None => ProgramState::config_path(&game, &profile),
+                        };
                         println!("{}", config_path.display());
                     }
+                    Lock => {
+                        let lock_path = match &project {
+                            Some(project) => project.lock_path(),
+                            None => ProgramState::lock_path(&game, &profile),
+                        };
+                        println!("{}", lock_path.display());
+                    }
                 }
             }
         }
     } else {
-        let mut program_state = ProgramState::from_cache(args);
+        let mut program_state = ProgramState::from_cache(args)?;
         main_menu::view(&thunderstore_api, &mut program_state).await?;
     };
 
@@ -1,7 +1,8 @@
 mod installed_mods;
 mod mod_search;
+mod profiles;
 
-pub use installed_mods::packages::Requirements;
+pub use installed_mods::packages::{LockedPackage, Lockfile, Requirement, Requirements};
 
 use crate::prelude::*;
 
@@ -11,6 +12,8 @@ enum_select! {
     enum MainMenuSelection {
         ViewInstalledMods = "View Installed Mods",
         ModSearch = "Mod Search",
+        WhatsNew = "What's New",
+        SwitchProfile = "Switch Profile",
         UpdateCache = "Update Thunderstore Mod Cache",
         Quit = "Quit",
     }
@@ -26,7 +29,12 @@ pub async fn view(
 
         match MainMenuSelection::selectable("Main Menu")
             .with_help_message(&format!(
-                "Last cache update: {}",
+                "{} | Last cache update: {}",
+                program_args
+                    .project
+                    .as_ref()
+                    .map(|manifest_path| format!("Project: {}", manifest_path.display()))
+                    .unwrap_or(format!("Profile: {}", program_args.profile)),
                 program_args
                     .last_updated
                     .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
@@ -40,6 +48,12 @@ pub async fn view(
             ModSearch => {
                 mod_search::view(program_args, api).await?;
             }
+            WhatsNew => {
+                crate::whats_new::view(program_args)?;
+            }
+            SwitchProfile => {
+                profiles::view(program_args)?;
+            }
             UpdateCache => {
                 clearscreen::clear()?;
                 println!("Refreshing packages...");
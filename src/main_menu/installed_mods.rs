@@ -1,42 +1,85 @@
 pub mod packages;
 
+use crate::health::{HealthCheck, Problem};
 use crate::prelude::*;
-use anyhow::anyhow;
+use crate::utils::InstalledMod;
 use colored::Colorize;
 use pad::{Alignment, PadStr};
-use std::{cmp::Ordering, collections::VecDeque, path::PathBuf};
-use thunderstore::models::PackageVersionV1;
+use std::cmp::Ordering;
+use thunderstore::{VersionIdent, models::PackageVersionV1};
 
 #[derive(Debug)]
 struct ModDirWithMetadata {
-    dir: PathBuf,
-    metadata: ModManifest,
-    namespaced: NamespacedPackage,
+    installed: InstalledMod,
     updated_version: Option<PackageVersionV1>,
+    held: bool,
+    problems: Vec<Problem>,
 }
 
 impl std::fmt::Display for ModDirWithMetadata {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let update_version = match &self.updated_version {
-            Some(updated) => updated
-                .ident
-                .parsed_version()
+        let name = self
+            .installed
+            .metadata
+            .name
+            .pad(20, ' ', Alignment::Middle, true);
+        let version =
+            self.installed
+                .metadata
+                .version
                 .to_string()
-                .pad(20, ' ', Alignment::Middle, true)
-                .red(),
-            None => "N/A".pad(20, ' ', Alignment::Middle, true).green(),
+                .pad(20, ' ', Alignment::Middle, true);
+        let update_version = match &self.updated_version {
+            Some(updated) => {
+                updated
+                    .ident
+                    .parsed_version()
+                    .to_string()
+                    .pad(20, ' ', Alignment::Middle, true)
+            }
+            None => "N/A".pad(20, ' ', Alignment::Middle, true),
         };
 
-        write!(
-            f,
-            "|{}|{}|{}|",
-            self.metadata.name.pad(20, ' ', Alignment::Middle, true),
-            self.metadata
-                .version
-                .to_string()
-                .pad(20, ' ', Alignment::Middle, true),
-            update_version
-        )
+        let status = self
+            .problems
+            .first()
+            .map(Problem::status)
+            .unwrap_or("OK")
+            .pad(16, ' ', Alignment::Middle, true);
+
+        // Disabled mods are greyed out entirely rather than highlighting their update status
+        if !self.installed.enabled {
+            return write!(
+                f,
+                "{}",
+                format!("|{}|{}|{}|{}|", name, version, update_version, status).dimmed()
+            );
+        }
+
+        let update_version = match (&self.updated_version, self.held) {
+            (_, true) => update_version.yellow(),
+            (Some(_), false) => update_version.red(),
+            (None, false) => update_version.green(),
+        };
+
+        let status = match self.problems.first() {
+            None => status.green(),
+            Some(Problem::Stale(_)) => status.yellow(),
+            Some(_) => status.red(),
+        };
+
+        write!(f, "|{}|{}|{}|{}|", name, version, update_version, status)
+    }
+}
+
+enum_select! {
+    /// The actions which can be taken on a selected installed mod
+    #[derive(Clone, Copy)]
+    enum InstalledModAction {
+        Enable = "Enable Mod",
+        Disable = "Disable Mod",
+        Changes = "View Changes Since Installed Version",
+        Back = "Back",
     }
 }
 
@@ -45,69 +88,43 @@ pub async fn view(
     api: &thunderstore::Client,
 ) -> anyhow::Result<()> {
     if program_state.packages.is_empty() {
-        api.list_packages_v1(&program_state.managed_game).await?;
+        clearscreen::clear()?;
+        println!("Please wait, fetching downloadable packages from Thunderstore...");
+        program_state.refresh_packages(api).await?;
     }
 
     loop {
         clearscreen::clear()?;
-        let mut installed_mods = Vec::new();
-        let mut dirs = VecDeque::new();
-        dirs.push_back(program_state.mods_dir.clone());
-
-        while let Some(dir) = dirs.pop_front() {
-            for entry in std::fs::read_dir(dir)? {
-                let entry = entry?;
-                let metadata = std::fs::metadata(entry.path())?;
-
-                if metadata.is_dir() {
-                    dirs.push_back(entry.path());
-                } else if entry.path().extension().unwrap_or_default() == "json" {
-                    let file_str = std::fs::read_to_string(entry.path())?;
-
-                    let parent_folder = entry
-                        .path()
-                        .parent()
-                        .ok_or(anyhow!("Unable to get parent folder for installed mod"))?
-                        .to_path_buf();
-
-                    let folder_name = parent_folder
-                        .iter()
-                        .next_back()
-                        .ok_or(anyhow!("invalid dir"))?
-                        .to_str()
-                        .ok_or(anyhow!("Invalid characters in dir name"))?;
-
-                    let mut splits = folder_name.split('-');
-                    let namespace = splits.next().ok_or(anyhow!("Invalid directory name"))?;
-                    let name = splits.next().ok_or(anyhow!("Invalid directory name"))?;
-
-                    if let Ok(mod_manifest) = serde_json::from_str::<ModManifest>(&file_str) {
-                        installed_mods.push(ModDirWithMetadata {
-                            metadata: mod_manifest,
-                            namespaced: NamespacedPackage::new(namespace, name),
-                            dir: entry
-                                .path()
-                                .parent()
-                                .ok_or(anyhow::anyhow!(
-                                    "Unable to get parent folder for installed mod"
-                                ))?
-                                .into(),
-                            updated_version: None,
-                        });
-                    }
-                }
-            }
-        }
+        let found = crate::utils::get_installed_mods(program_state)?;
+        let health = HealthCheck::new(
+            program_state,
+            &found,
+            program_state.settings.stale_after_months,
+        );
+        let problems = found
+            .iter()
+            .map(|installed| health.problems(installed))
+            .collect::<Vec<_>>();
+        let mut installed_mods = found
+            .into_iter()
+            .zip(problems)
+            .map(|(installed, problems)| ModDirWithMetadata {
+                held: program_state.requirements.is_held(&installed.namespaced),
+                installed,
+                updated_version: None,
+                problems,
+            })
+            .collect::<Vec<_>>();
 
         for installed in &mut installed_mods {
             let Some(latest) = program_state
                 .requirements
-                .get_latest_version(&program_state.packages, &installed.namespaced)
+                .get_latest_version(&program_state.packages, &installed.installed.namespaced)
             else {
                 continue;
             };
 
-            if latest.ident.parsed_version() > installed.metadata.version {
+            if latest.ident.parsed_version() > installed.installed.metadata.version {
                 installed.updated_version = Some(latest);
             }
         }
@@ -123,10 +140,11 @@ pub async fn view(
 
         let selected_option = inquire::Select::new("Installed mods...", installed_mods)
             .with_help_message(&format!(
-                " |{}|{}|{}| ",
+                " |{}|{}|{}|{}| ",
                 "Name".pad_to_width_with_alignment(20, Alignment::Middle),
                 "Installed Version".pad_to_width_with_alignment(20, Alignment::Middle),
-                "Update Version".pad_to_width_with_alignment(20, Alignment::Middle)
+                "Update Version".pad_to_width_with_alignment(20, Alignment::Middle),
+                "Status".pad_to_width_with_alignment(16, Alignment::Middle)
             ))
             .with_page_size(height - 2)
             .prompt_skippable()?;
@@ -138,6 +156,82 @@ pub async fn view(
                 continue;
             }
         };
+
+        let actions = InstalledModAction::VARIANTS
+            .iter()
+            .copied()
+            .filter(|action| match action {
+                InstalledModAction::Enable => !selected_option.installed.enabled,
+                InstalledModAction::Disable => selected_option.installed.enabled,
+                InstalledModAction::Changes => selected_option.updated_version.is_some(),
+                InstalledModAction::Back => true,
+            })
+            .collect::<Vec<_>>();
+
+        let namespaced = &selected_option.installed.namespaced;
+        for problem in &selected_option.problems {
+            println!("{} {}", namespaced, problem);
+        }
+        let action = inquire::Select::new(&namespaced.to_string(), actions)
+            .prompt_skippable()?
+            .unwrap_or(InstalledModAction::Back);
+        match action {
+            InstalledModAction::Enable => {
+                crate::toggle::set_enabled(program_state, namespaced, true)?;
+            }
+            InstalledModAction::Disable => {
+                crate::toggle::set_enabled(program_state, namespaced, false)?;
+            }
+            InstalledModAction::Changes => {
+                if let Some(target) = &selected_option.updated_version {
+                    view_changes(api, &selected_option.installed, &target.ident).await?;
+                }
+            }
+            InstalledModAction::Back => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Pages through the changelog entries between the installed version of a mod and its update
+async fn view_changes(
+    api: &thunderstore::Client,
+    installed: &InstalledMod,
+    target: &VersionIdent,
+) -> anyhow::Result<()> {
+    clearscreen::clear()?;
+    println!("Fetching the changelog of {}...", target);
+
+    let title = format!(
+        "Changes to {} from {} to {}",
+        installed.namespaced,
+        installed.metadata.version,
+        target.version()
+    );
+    match crate::docs::changes_since(api, &installed.metadata.version, target).await? {
+        crate::docs::Changes::Entries(changes) if !changes.trim().is_empty() => {
+            crate::docs::page(&title, &changes)?
+        }
+        changes => {
+            println!(
+                "{}",
+                match changes {
+                    crate::docs::Changes::NoChangelog => {
+                        "This version was not published with a changelog"
+                    }
+                    crate::docs::Changes::NoHeadings => {
+                        "The changelog doesn't list its changes by version"
+                    }
+                    crate::docs::Changes::Entries(_) => {
+                        "The changelog has no entries for these versions"
+                    }
+                }
+            );
+            inquire::Confirm::new("Press enter to continue")
+                .with_default(true)
+                .prompt_skippable()?;
+        }
     }
 
     Ok(())
@@ -1,9 +1,153 @@
 use crate::prelude::*;
-use serde::Deserialize;
-use std::collections::HashMap;
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::BufReader,
+    path::Path,
+};
 
-#[derive(Deserialize, Default, Debug)]
-pub struct Requirements(HashMap<NamespacedPackage, semver::VersionReq>);
+/// A single entry of the requirements file. Entries are written as a bare version range unless
+/// they carry any extra state, in which case they are written as an object.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(from = "RequirementRepr", into = "RequirementRepr")]
+pub struct Requirement {
+    /// The range of versions which may be installed
+    pub version: semver::VersionReq,
+    /// Whether the mod is loaded by the game, or has been moved to the disabled mods folder
+    pub enabled: bool,
+    /// Held mods are kept at their installed version and are skipped by updates
+    pub held: bool,
+    /// Why the mod is being held, if a reason was given
+    pub hold_reason: Option<String>,
+    /// A free form note explaining why the mod is required
+    pub note: Option<String>,
+    /// Whether the mod is only needed on the server, and not by players joining it
+    pub server_only: bool,
+}
+
+impl Requirement {
+    pub fn new(version: semver::VersionReq) -> Self {
+        Self {
+            version,
+            enabled: true,
+            held: false,
+            hold_reason: None,
+            note: None,
+            server_only: false,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum RequirementRepr {
+    Bare(semver::VersionReq),
+    Full(FullRequirement),
+}
+
+#[derive(Deserialize, Serialize)]
+struct FullRequirement {
+    version: semver::VersionReq,
+    #[serde(default = "default_enabled", skip_serializing_if = "is_enabled")]
+    enabled: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    held: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hold_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    server_only: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn is_enabled(enabled: &bool) -> bool {
+    *enabled
+}
+
+/// Deserialized by hand rather than as an untagged enum, so that a malformed entry reports what is
+/// actually wrong with it
+impl<'de> Deserialize<'de> for RequirementRepr {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct ReprVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for ReprVisitor {
+            type Value = RequirementRepr;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a version range, or a map with a version field")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                value.parse().map(RequirementRepr::Bare).map_err(E::custom)
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                FullRequirement::deserialize(serde::de::value::MapAccessDeserializer::new(map))
+                    .map(RequirementRepr::Full)
+            }
+        }
+
+        deserializer.deserialize_any(ReprVisitor)
+    }
+}
+
+impl From<RequirementRepr> for Requirement {
+    fn from(value: RequirementRepr) -> Self {
+        match value {
+            RequirementRepr::Bare(version) => Self::new(version),
+            RequirementRepr::Full(FullRequirement {
+                version,
+                enabled,
+                held,
+                hold_reason,
+                note,
+                server_only,
+            }) => Self {
+                version,
+                enabled,
+                held,
+                hold_reason,
+                note,
+                server_only,
+            },
+        }
+    }
+}
+
+impl From<Requirement> for RequirementRepr {
+    fn from(value: Requirement) -> Self {
+        if value == Requirement::new(value.version.clone()) {
+            return Self::Bare(value.version);
+        }
+
+        Self::Full(FullRequirement {
+            version: value.version,
+            enabled: value.enabled,
+            held: value.held,
+            hold_reason: value.hold_reason,
+            note: value.note,
+            server_only: value.server_only,
+        })
+    }
+}
+
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct Requirements(HashMap<NamespacedPackage, Requirement>);
 
 impl Requirements {
     pub fn get_latest_version(
@@ -12,7 +156,7 @@ impl Requirements {
         for_mod: &NamespacedPackage,
     ) -> Option<thunderstore::models::PackageVersionV1> {
         let remote_versions = packages.get(for_mod).map(|pkgs| &pkgs.versions)?;
-        let requested_version = self.0.get(for_mod)?;
+        let requested_version = &self.0.get(for_mod)?.version;
 
         let mut matched_versions = remote_versions
             .iter()
@@ -22,4 +166,176 @@ impl Requirements {
         matched_versions.sort_by_key(|version| version.ident.parsed_version());
         matched_versions.last().cloned().cloned()
     }
+
+    /// Gets the requirement for a mod, if it has been explicitly required
+    pub fn get(&self, for_mod: &NamespacedPackage) -> Option<&Requirement> {
+        self.0.get(for_mod)
+    }
+
+    pub fn get_mut(&mut self, for_mod: &NamespacedPackage) -> Option<&mut Requirement> {
+        self.0.get_mut(for_mod)
+    }
+
+    /// Sets the requested version range for a mod, keeping any other state of an existing entry
+    pub fn insert(&mut self, for_mod: NamespacedPackage, version: semver::VersionReq) {
+        self.0
+            .entry(for_mod)
+            .and_modify(|requirement| requirement.version = version.clone())
+            .or_insert_with(|| Requirement::new(version));
+    }
+
+    /// Removes a mod from the requirements, returning its previous requirement
+    pub fn remove(&mut self, for_mod: &NamespacedPackage) -> Option<Requirement> {
+        self.0.remove(for_mod)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&NamespacedPackage, &Requirement)> {
+        self.0.iter()
+    }
+
+    /// Whether a mod is being held at its installed version
+    pub fn is_held(&self, for_mod: &NamespacedPackage) -> bool {
+        self.0
+            .get(for_mod)
+            .is_some_and(|requirement| requirement.held)
+    }
+}
+
+/// A record of exactly what was installed into the mods directory for a single package
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct LockedPackage {
+    /// The installed version of the package
+    pub version: semver::Version,
+    /// Every file extracted from the package archive, relative to the package's install folder,
+    /// mapped to the sha256 hash of its original contents
+    pub files: BTreeMap<String, String>,
+    /// Versions which were installed before this one, most recent first
+    #[serde(default)]
+    pub previous: Vec<semver::Version>,
+}
+
+/// The schema version written to lock files
+const LOCKFILE_SCHEMA: u32 = 1;
+
+/// The set of packages currently installed by this program, along with the file hashes recorded
+/// at install time.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(from = "LockfileRepr")]
+pub struct Lockfile {
+    /// Lock files written before the schema was versioned are read as version 0
+    schema_version: u32,
+    packages: HashMap<NamespacedPackage, LockedPackage>,
+    /// Every package changed by the most recent update, along with the version it had before the
+    /// update. `None` means the package was not installed beforehand.
+    #[serde(default)]
+    pub last_operation: Vec<(NamespacedPackage, Option<semver::Version>)>,
+}
+
+/// The layouts a lock file has been written in
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LockfileRepr {
+    Current {
+        #[serde(default)]
+        schema_version: u32,
+        packages: HashMap<NamespacedPackage, LockedPackage>,
+        #[serde(default)]
+        last_operation: Vec<(NamespacedPackage, Option<semver::Version>)>,
+    },
+    /// Lock files written before rollbacks were added hold nothing but the packages
+    Packages(HashMap<NamespacedPackage, LockedPackage>),
+}
+
+impl From<LockfileRepr> for Lockfile {
+    fn from(repr: LockfileRepr) -> Self {
+        match repr {
+            LockfileRepr::Current {
+                schema_version,
+                packages,
+                last_operation,
+            } => Self {
+                schema_version,
+                packages,
+                last_operation,
+            },
+            LockfileRepr::Packages(packages) => Self {
+                schema_version: 0,
+                packages,
+                last_operation: Vec::new(),
+            },
+        }
+    }
+}
+
+impl Default for Lockfile {
+    fn default() -> Self {
+        Self {
+            schema_version: LOCKFILE_SCHEMA,
+            packages: HashMap::new(),
+            last_operation: Vec::new(),
+        }
+    }
+}
+
+impl Lockfile {
+    /// Reads a lock file, whichever schema it was written with. It is written back with the
+    /// current schema the next time it is saved. A file which doesn't exist yet holds no packages.
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        if !std::fs::exists(path)? {
+            return Ok(Self::default());
+        }
+
+        let mut lockfile: Self = serde_json::from_reader(BufReader::new(File::open(path)?))
+            .map_err(|err| anyhow!("Unable to read {}: {}", path.display(), err))?;
+        crate::requirements::check_schema(path, lockfile.schema_version, LOCKFILE_SCHEMA)?;
+        lockfile.schema_version = LOCKFILE_SCHEMA;
+        Ok(lockfile)
+    }
+
+    pub fn get(&self, for_mod: &NamespacedPackage) -> Option<&LockedPackage> {
+        self.packages.get(for_mod)
+    }
+
+    pub fn get_mut(&mut self, for_mod: &NamespacedPackage) -> Option<&mut LockedPackage> {
+        self.packages.get_mut(for_mod)
+    }
+
+    pub fn insert(&mut self, for_mod: NamespacedPackage, locked: LockedPackage) {
+        self.packages.insert(for_mod, locked);
+    }
+
+    pub fn remove(&mut self, for_mod: &NamespacedPackage) -> Option<LockedPackage> {
+        self.packages.remove(for_mod)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&NamespacedPackage, &LockedPackage)> {
+        self.packages.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+
+    #[test]
+    fn lock_files_written_before_schema_versions_are_upgraded() {
+        let dir = TestDir::new("lock_files_written_before_schema_versions_are_upgraded");
+        let path = dir.join("unversioned.lock");
+        std::fs::write(
+            &path,
+            r#"{ "A/B": { "version": "1.0.0", "files": {}, "previous": [] } }"#,
+        )
+        .unwrap();
+
+        let lockfile = Lockfile::read(&path).unwrap();
+
+        assert!(
+            lockfile
+                .get(&NamespacedPackage::value_parser("A/B").unwrap())
+                .is_some()
+        );
+        let written = serde_json::to_value(&lockfile).unwrap();
+        assert_eq!(written["schema_version"], LOCKFILE_SCHEMA);
+    }
 }
@@ -1,7 +1,177 @@
 mod mod_details;
 mod mod_install;
 
-use crate::prelude::*;
+use crate::{
+    docs::Document,
+    prelude::*,
+    search::{SavedSearch, SearchFilters, SearchIndex, SortOrder},
+    utils,
+};
+use chrono::{DateTime, Local};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+};
+
+/// A line of the search screen: either a package, or one of the options controlling which packages
+/// are listed and how they are installed
+#[derive(Clone)]
+enum SearchEntry<'a> {
+    Filters(String),
+    Sort(SortOrder),
+    InstallSeveral,
+    Package(&'a SearchablePackage, SortOrder),
+}
+
+impl std::fmt::Display for SearchEntry<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Filters(summary) => write!(f, "[ Filters: {} ]", summary),
+            Self::Sort(sort) => write!(f, "[ Sort by: {} ]", sort),
+            Self::InstallSeveral => write!(f, "[ Install several packages ]"),
+            Self::Package(package, sort) => write!(f, "{}", package.table_row(*sort)),
+        }
+    }
+}
+
+/// Ranks the listed packages against what has been typed. inquire scores every option on its own,
+/// so the whole query is ranked once and the scores of each option looked up from that.
+struct Ranking {
+    /// The packages the index was built from, in order, and the cache refresh they came from
+    listing: (Option<DateTime<Local>>, Vec<NamespacedPackage>),
+    index: SearchIndex,
+    ranked: RefCell<(String, HashMap<usize, i64>)>,
+}
+
+impl Ranking {
+    fn new(state: &crate::ProgramState, packages: &[&SearchablePackage]) -> Self {
+        Self {
+            listing: Self::listing(state, packages),
+            index: SearchIndex::build(packages.iter().copied()),
+            ranked: RefCell::default(),
+        }
+    }
+
+    fn listing(
+        state: &crate::ProgramState,
+        packages: &[&SearchablePackage],
+    ) -> (Option<DateTime<Local>>, Vec<NamespacedPackage>) {
+        (
+            state.last_updated,
+            packages
+                .iter()
+                .map(|package| NamespacedPackage::from(*package))
+                .collect(),
+        )
+    }
+
+    /// Whether the ranking was built from the same packages, so it can be used again
+    fn ranks(&self, state: &crate::ProgramState, packages: &[&SearchablePackage]) -> bool {
+        self.listing == Self::listing(state, packages)
+    }
+
+    /// The score of the package at `position` in the listing, `None` if it doesn't match. Unless
+    /// the packages are sorted by relevance, matches keep the order of the listing.
+    fn score(&self, input: &str, position: usize, sort: SortOrder) -> Option<i64> {
+        let score = self.relevance(input, position)?;
+        if sort != SortOrder::Relevance {
+            return Some(-(position as i64));
+        }
+        Some(score)
+    }
+
+    fn relevance(&self, input: &str, position: usize) -> Option<i64> {
+        let mut ranked = self.ranked.borrow_mut();
+        if ranked.0 != input {
+            *ranked = (
+                input.to_owned(),
+                self.index
+                    .search(input)
+                    .into_iter()
+                    .map(|(position, score)| (position, (score * 1000.0) as i64))
+                    .collect(),
+            );
+        }
+        ranked.1.get(&position).copied()
+    }
+}
+
+/// The packages passing the filters, in the chosen order
+fn listed_packages<'a>(
+    state: &'a crate::ProgramState,
+    filters: &SearchFilters,
+    sort: SortOrder,
+) -> anyhow::Result<Vec<&'a SearchablePackage>> {
+    let installed = if filters.hide_installed {
+        utils::get_installed_mods(state)?
+            .into_iter()
+            .map(|installed| installed.namespaced)
+            .collect()
+    } else {
+        HashSet::new()
+    };
+
+    let mut packages = state
+        .packages
+        .values()
+        .filter(|package| filters.matches(package, &installed))
+        .collect::<Vec<_>>();
+    packages.sort_by(|a, b| sort.compare(a, b));
+    Ok(packages)
+}
+
+/// Lets the user pick any number of the listed packages, searching as on the main search screen,
+/// and installs the latest versions of them together after a review
+async fn install_several(
+    state: &mut crate::ProgramState,
+    api: &thunderstore::Client,
+    filters: &SearchFilters,
+    sort: SortOrder,
+) -> anyhow::Result<()> {
+    clearscreen::clear()?;
+    let (_, height) = term_size::dimensions().unwrap_or((60, 60));
+
+    let packages = listed_packages(state, filters, sort)?;
+    let ranking = Ranking::new(state, &packages);
+    let entries = packages
+        .into_iter()
+        .map(|package| SearchEntry::Package(package, sort))
+        .collect::<Vec<_>>();
+
+    let scorer = |input: &str, _: &SearchEntry, _: &str, position: usize| {
+        if input.trim().is_empty() {
+            return Some(-(position as i64));
+        }
+        ranking.score(input, position, sort)
+    };
+
+    let Some(selected) = inquire::MultiSelect::new(
+        "Type to search, press <space> to pick packages and <enter> to review them, or <esc> to go back",
+        entries,
+    )
+    .with_page_size(height.saturating_sub(2).max(1))
+    .with_scorer(&scorer)
+    .with_help_message(&SearchablePackage::table_header(sort))
+    .prompt_skippable()?
+    else {
+        return Ok(());
+    };
+
+    let to_install = selected
+        .iter()
+        .filter_map(|entry| match entry {
+            SearchEntry::Package(package, _) => crate::info::versions_newest_first(package)
+                .first()
+                .map(|latest| (*latest).clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if to_install.is_empty() {
+        return Ok(());
+    }
+
+    crate::install::review_and_install(state, api, &to_install).await
+}
 
 pub async fn view(
     state: &mut crate::ProgramState,
@@ -13,18 +183,56 @@ pub async fn view(
         state.refresh_packages(api).await?;
     }
 
+    let mut search = SavedSearch::load(&state.managed_game)?;
+    // Building the index is the slow part of listing, so it is only done again once the filters,
+    // the sort order or the cache change what is listed
+    let mut cached_ranking: Option<Ranking> = None;
+
     loop {
         clearscreen::clear()?;
         let (_, height) = term_size::dimensions().unwrap_or((60, 60));
 
-        let mut to_display = state.packages.values().cloned().collect::<Vec<_>>();
-        to_display.sort_by_key(|item| std::cmp::Reverse(item.total_downloads()));
+        let to_display = listed_packages(state, &search.filters, search.sort)?;
+        let ranking = match cached_ranking.take() {
+            Some(ranking) if ranking.ranks(state, &to_display) => cached_ranking.insert(ranking),
+            _ => cached_ranking.insert(Ranking::new(state, &to_display)),
+        };
+        let mut entries = vec![
+            SearchEntry::Filters(format!(
+                "{} ({} of {} packages)",
+                search.filters,
+                to_display.len(),
+                state.packages.len()
+            )),
+            SearchEntry::Sort(search.sort),
+            SearchEntry::InstallSeveral,
+        ];
+        let first_package = entries.len();
+        entries.extend(
+            to_display
+                .into_iter()
+                .map(|package| SearchEntry::Package(package, search.sort)),
+        );
 
-        let Some(selected_option) =
-            inquire::Select::new("Online mods. Press <esc> to cancel", to_display)
-                .with_page_size(height - 2)
-                .with_help_message(" |       Name       |    Downloads    |    Rating    | ")
-                .prompt_skippable()?
+        let scorer = |input: &str, entry: &SearchEntry, _: &str, position: usize| {
+            if input.trim().is_empty() {
+                return Some(-(position as i64));
+            }
+            // The controls move below the matches, so that enter picks the best match
+            let SearchEntry::Package(..) = entry else {
+                return Some(i64::MIN + (first_package - position) as i64);
+            };
+            ranking.score(input, position - first_package, search.sort)
+        };
+
+        let Some(selected_option) = inquire::Select::new(
+            "Online mods. Type to search, press <esc> to cancel",
+            entries,
+        )
+        .with_page_size(height - 2)
+        .with_scorer(&scorer)
+        .with_help_message(&SearchablePackage::table_header(search.sort))
+        .prompt_skippable()?
         else {
             if !crate::back_dialog::view()? {
                 break;
@@ -33,11 +241,57 @@ pub async fn view(
             }
         };
 
-        match mod_details::view(&selected_option)? {
-            mod_details::ModDetailsResult::Install => {
-                mod_install::view(&selected_option, api).await?;
+        let selected_option = match selected_option {
+            SearchEntry::Filters(_) => {
+                let categories = SearchFilters::categories(state.packages.values());
+                search.filters = crate::search::edit_filters(search.filters, &categories)?;
+                search.save(&state.managed_game)?;
+                continue;
+            }
+            SearchEntry::Sort(current) => {
+                let sorts = SortOrder::VARIANTS.to_vec();
+                let cursor = sorts.iter().position(|candidate| *candidate == current);
+                if let Some(selected) = inquire::Select::new("Sort packages by", sorts)
+                    .with_starting_cursor(cursor.unwrap_or_default())
+                    .prompt_skippable()?
+                {
+                    search.sort = selected;
+                    search.save(&state.managed_game)?;
+                }
+                continue;
+            }
+            SearchEntry::InstallSeveral => {
+                install_several(state, api, &search.filters, search.sort).await?;
+                continue;
+            }
+            SearchEntry::Package(package, _) => package.clone(),
+        };
+
+        let latest = crate::info::versions_newest_first(&selected_option)
+            .first()
+            .map(|latest| (*latest).clone());
+        // Reading the docs or browsing the author comes back to the details, everything else
+        // back to the search
+        let to_install = loop {
+            let document = match mod_details::view(state, &selected_option)? {
+                mod_details::ModDetailsResult::Install => break latest,
+                mod_details::ModDetailsResult::InstallVersion => {
+                    break mod_details::pick_version(&selected_option)?;
+                }
+                mod_details::ModDetailsResult::Readme => Document::Readme,
+                mod_details::ModDetailsResult::Changelog => Document::Changelog,
+                mod_details::ModDetailsResult::Author => {
+                    crate::author::view(state, api, &selected_option.namespace).await?;
+                    continue;
+                }
+                mod_details::ModDetailsResult::Back => break None,
+            };
+            if let Some(latest) = &latest {
+                crate::docs::view(api, &latest.ident, document).await?;
             }
-            mod_details::ModDetailsResult::Back => {}
+        };
+        if let Some(version) = to_install {
+            mod_install::view(state, &version, api).await?;
         }
     }
 
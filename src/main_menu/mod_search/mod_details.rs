@@ -1,15 +1,23 @@
 use crate::prelude::*;
-use thunderstore::VersionIdent;
+use std::collections::{HashMap, HashSet};
+use thunderstore::{VersionIdent, models::PackageVersionV1};
 
 enum_select! {
     #[derive(Clone)]
     pub enum ModDetailsResult {
         Back = "Back",
         Install = "Install Mod",
+        InstallVersion = "Install Another Version",
+        Readme = "Read README",
+        Changelog = "Read Changelog",
+        Author = "More From This Author",
     }
 }
 
-pub fn view(to_view: &super::SearchablePackage) -> anyhow::Result<ModDetailsResult> {
+pub fn view(
+    state: &crate::ProgramState,
+    to_view: &super::SearchablePackage,
+) -> anyhow::Result<ModDetailsResult> {
     clearscreen::clear()?;
 
     let mut versions = to_view.versions.clone();
@@ -20,23 +28,140 @@ pub fn view(to_view: &super::SearchablePackage) -> anyhow::Result<ModDetailsResu
         .ok_or(anyhow::anyhow!("This mod has no versions"))?;
 
     println!("Mod Name: {}", to_view.0.name);
-    println!("Mod Author: {}", to_view.0.namespace);
     println!("Version: {}", latest_version.ident.version());
     println!("Description: {}", latest_version.description);
+    crate::info::print_metadata(to_view);
     println!("--- Dependencies ---");
 
-    for dep in &latest_version.dependencies {
-        print_dependencies(dep, "  ");
-        println!("  --------------------");
+    if latest_version.dependencies.is_empty() {
+        println!("None");
     }
+    let mut expanded = HashSet::from([NamespacedPackage::from(to_view)]);
+    print_dependencies(
+        &state.packages,
+        &latest_version.dependencies,
+        "",
+        &mut expanded,
+    );
+
+    print_used_by(state, to_view);
+    crate::info::print_version_history(to_view, Some(VERSION_HISTORY_LIMIT));
 
     let return_result = ModDetailsResult::selectable("Options").prompt()?;
 
     Ok(return_result)
 }
 
-fn print_dependencies(package: &VersionIdent, prefix: &str) {
-    println!("{}Mod Name: {}", prefix, package.package_id().name());
-    println!("{}Mod Author: {}", prefix, package.namespace());
-    println!("{}Version: {}", prefix, package.version());
+/// A version of a package as listed when picking a version to install
+struct VersionOption<'a>(&'a PackageVersionV1);
+
+impl std::fmt::Display for VersionOption<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}, {} downloads, {})",
+            self.0.number,
+            self.0.date_created.format("%Y-%m-%d"),
+            self.0.downloads,
+            crate::info::format_size(self.0.file_size)
+        )
+    }
+}
+
+/// Lets the user pick any version of a package, newest first
+pub fn pick_version(to_view: &SearchablePackage) -> anyhow::Result<Option<PackageVersionV1>> {
+    let versions = crate::info::versions_newest_first(to_view)
+        .into_iter()
+        .map(VersionOption)
+        .collect::<Vec<_>>();
+
+    Ok(
+        inquire::Select::new("Which version should be installed?", versions)
+            .prompt_skippable()?
+            .map(|selected| selected.0.clone()),
+    )
+}
+
+/// How many dependents are listed before the rest are summarised
+const USED_BY_LIMIT: usize = 10;
+
+/// How many of the newest versions are listed, so the options stay on screen. Every version can be
+/// seen when picking another version to install.
+const VERSION_HISTORY_LIMIT: usize = 3;
+
+/// Prints the most downloaded packages which depend on the viewed mod
+fn print_used_by(state: &crate::ProgramState, to_view: &SearchablePackage) {
+    let dependents = state
+        .reverse_dependencies()
+        .dependents_of(&state.packages, &NamespacedPackage::from(to_view));
+
+    println!("--- Used by ---");
+    if dependents.is_empty() {
+        println!("None");
+    }
+    for dependent in dependents.iter().take(USED_BY_LIMIT) {
+        let namespaced = NamespacedPackage::from(dependent.package);
+        println!(
+            "{} ({} downloads){}",
+            namespaced,
+            dependent.package.total_downloads(),
+            if state.lockfile.get(&namespaced).is_some() {
+                " [installed]"
+            } else {
+                ""
+            }
+        );
+    }
+    if dependents.len() > USED_BY_LIMIT {
+        println!("...and {} more", dependents.len() - USED_BY_LIMIT);
+    }
+}
+
+/// Prints the dependencies as a tree, following each dependency to the newest version of it in
+/// the index. Packages already expanded elsewhere in the tree are not expanded again.
+fn print_dependencies(
+    packages: &HashMap<NamespacedPackage, SearchablePackage>,
+    dependencies: &[VersionIdent],
+    prefix: &str,
+    expanded: &mut HashSet<NamespacedPackage>,
+) {
+    for (index, dependency) in dependencies.iter().enumerate() {
+        let is_last = index == dependencies.len() - 1;
+        let namespaced = NamespacedPackage::from(dependency);
+        let latest = packages.get(&namespaced).and_then(|package| {
+            package
+                .versions
+                .iter()
+                .max_by_key(|version| version.ident.parsed_version())
+        });
+
+        let status = match latest {
+            Some(latest) if !expanded.contains(&namespaced) => {
+                format!("latest {}", latest.ident.version())
+            }
+            Some(latest) if !latest.dependencies.is_empty() => {
+                format!(
+                    "latest {}, dependencies shown above",
+                    latest.ident.version()
+                )
+            }
+            Some(latest) => format!("latest {}", latest.ident.version()),
+            None => "not found on Thunderstore".into(),
+        };
+        println!(
+            "{}{} {} >= {} ({})",
+            prefix,
+            if is_last { "└─" } else { "├─" },
+            namespaced,
+            dependency.version(),
+            status
+        );
+
+        if let Some(latest) = latest
+            && expanded.insert(namespaced)
+        {
+            let child_prefix = format!("{}{}", prefix, if is_last { "   " } else { "│  " });
+            print_dependencies(packages, &latest.dependencies, &child_prefix, expanded);
+        }
+    }
 }
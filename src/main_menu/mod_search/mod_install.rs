@@ -1,6 +1,17 @@
+use thunderstore::models::PackageVersionV1;
+
 pub async fn view(
-    to_install: &super::SearchablePackage,
+    state: &mut crate::ProgramState,
+    version: &PackageVersionV1,
     api: &thunderstore::Client,
 ) -> anyhow::Result<()> {
+    clearscreen::clear()?;
+
+    crate::install::install_with_dependencies(state, api, version).await?;
+
+    inquire::Confirm::new("Installation complete. Press enter to continue")
+        .with_default(true)
+        .prompt_skippable()?;
+
     Ok(())
 }
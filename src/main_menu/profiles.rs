@@ -0,0 +1,35 @@
+use crate::profile::Profiles;
+use colored::Colorize;
+
+/// Lets the user pick which profile of the managed game to work with. The picked profile becomes
+/// the active profile for future sessions as well.
+pub fn view(program_state: &mut crate::ProgramState) -> anyhow::Result<()> {
+    clearscreen::clear()?;
+
+    let mut profiles = Profiles::load(&program_state.managed_game)?;
+    let names = profiles.profiles.keys().cloned().collect::<Vec<_>>();
+    let starting_cursor = names
+        .iter()
+        .position(|name| *name == program_state.profile)
+        .unwrap_or_default();
+
+    let Some(selected) = inquire::Select::new("Select a profile", names)
+        .with_starting_cursor(starting_cursor)
+        .with_help_message(&format!("Current profile: {}", program_state.profile))
+        .prompt_skippable()?
+    else {
+        return Ok(());
+    };
+
+    if let Err(err) = program_state.load_profile(&profiles, &selected, None, true) {
+        println!("{}", err.to_string().red());
+        inquire::Confirm::new("Press enter to continue")
+            .with_default(true)
+            .prompt_skippable()?;
+        return Ok(());
+    }
+    profiles.active = selected;
+    profiles.save(&program_state.managed_game)?;
+
+    Ok(())
+}
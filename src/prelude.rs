@@ -1,8 +1,9 @@
-pub use crate::main_menu::Requirements;
+pub use crate::main_menu::{LockedPackage, Lockfile, Requirement, Requirements};
+use crate::search::SortOrder;
 use directories::ProjectDirs;
 use inquire::Select;
 use pad::PadStr;
-use serde::{Deserialize, de::Visitor};
+use serde::{Deserialize, Serialize, de::Visitor};
 use std::{ops::Deref, path::PathBuf, sync::LazyLock};
 use thunderstore::{VersionIdent, models::PackageV1};
 
@@ -18,18 +19,28 @@ pub static CONFIG_DIR: LazyLock<PathBuf> = LazyLock::new(|| {
         .config_dir()
         .to_path_buf()
 });
+/// Downloaded package archives, keyed by their full version identifier
+pub static ARCHIVE_DIR: LazyLock<PathBuf> = LazyLock::new(|| CACHE_DIR.join("archives"));
 
 /// Represents a namespaced package as used as a key for the requirements.json file.
 ///
 /// ## Example
 /// "MyNamespace/PackageName"
-#[derive(Hash, PartialEq, Eq, Debug, Clone)]
+#[derive(Hash, PartialEq, Eq, PartialOrd, Ord, Debug, Clone)]
 pub struct NamespacedPackage(String, String);
 
 impl NamespacedPackage {
     /// For use with `clap` to parse command line arguments
     pub fn value_parser(value: &str) -> Result<Self, String> {
-        todo!()
+        let Some((namespace, name)) = value.split_once('/') else {
+            return Err("Expected a mod in the format of Namespace/Name".into());
+        };
+
+        if namespace.is_empty() || name.is_empty() || name.contains('/') {
+            return Err("Package namespace is malformed".into());
+        }
+
+        Ok(Self::new(namespace, name))
     }
 
     pub fn new(namespace: &str, name: &str) -> Self {
@@ -43,6 +54,25 @@ impl NamespacedPackage {
     pub fn name(&self) -> &str {
         &self.1
     }
+    /// The folder name this package is installed to within the mods directory
+    pub fn dir_name(&self) -> String {
+        format!("{}-{}", self.0, self.1)
+    }
+}
+
+impl std::fmt::Display for NamespacedPackage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.0, self.1)
+    }
+}
+
+impl Serialize for NamespacedPackage {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
 }
 
 impl<'de> serde::Deserialize<'de> for NamespacedPackage {
@@ -85,6 +115,12 @@ impl From<&PackageV1> for NamespacedPackage {
     }
 }
 
+impl From<&VersionIdent> for NamespacedPackage {
+    fn from(value: &VersionIdent) -> Self {
+        Self(value.namespace().to_owned(), value.name().to_owned())
+    }
+}
+
 impl From<&SearchablePackage> for NamespacedPackage {
     fn from(value: &SearchablePackage) -> Self {
         NamespacedPackage(value.namespace.clone(), value.name.clone())
@@ -99,7 +135,7 @@ where
     const VARIANTS: &'static [Self];
 
     /// Creates an `inquire::Select` struct from the variants of this enum
-    fn selectable(message: &str) -> Select<Self>;
+    fn selectable(message: &str) -> Select<'_, Self>;
 }
 
 /// Allows for quick creation of an enum with
@@ -128,19 +164,20 @@ macro_rules! enum_select {
     $(#[derive($($derive_target:ident),*)])?
     $vis:vis enum $enum_name:ident {
     $(
+        $(#[$variant_meta:meta])*
         $variant:ident = $display:expr,
     )*
     }) => {
         $(#[doc = $documentation])?
         $(#[derive($($derive_target),*)])?
         $vis enum $enum_name {
-            $($variant),*
+            $($(#[$variant_meta])* $variant),*
         }
 
         impl $crate::prelude::EnumSelectable for $enum_name {
             const VARIANTS: &'static [$enum_name] = &[$($enum_name::$variant),*];
 
-            fn selectable(message: &str) -> inquire::Select<Self> {
+            fn selectable(message: &str) -> inquire::Select<'_, Self> {
                 inquire::Select::new(message, Self::VARIANTS.to_vec())
             }
 
@@ -186,13 +223,56 @@ impl SearchablePackage {
     }
 }
 
-impl std::fmt::Display for SearchablePackage {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl SearchablePackage {
+    /// The header of the package table, marking the column the packages are sorted by
+    pub fn table_header(sort: SortOrder) -> String {
+        let title = |title: &str, sorted: bool| match (sorted, sort) {
+            (false, _) => title.to_owned(),
+            (true, SortOrder::Name) => format!("{} ▲", title),
+            (true, _) => format!("{} ▼", title),
+        };
+
+        format!(
+            " |{}|{}|{}|{}| ",
+            title("Name", sort == SortOrder::Name)
+                .pad_to_width_with_alignment(18, pad::Alignment::Middle),
+            title("Downloads", sort == SortOrder::Downloads)
+                .pad_to_width_with_alignment(17, pad::Alignment::Middle),
+            title("Rating", sort == SortOrder::Rating)
+                .pad_to_width_with_alignment(14, pad::Alignment::Middle),
+            title(
+                Self::extra_column(sort),
+                matches!(
+                    sort,
+                    SortOrder::Updated | SortOrder::Created | SortOrder::LatestDownloads
+                )
+            )
+            .pad_to_width_with_alignment(16, pad::Alignment::Middle)
+        )
+    }
+
+    /// The last column of the table shows the date or count being sorted by, or the date updated
+    /// when the packages are sorted by one of the other columns
+    fn extra_column(sort: SortOrder) -> &'static str {
+        match sort {
+            SortOrder::Created => "Created",
+            SortOrder::LatestDownloads => "Latest DLs",
+            _ => "Updated",
+        }
+    }
+
+    /// A row of the package table, as headed by [`SearchablePackage::table_header`]
+    pub fn table_row(&self, sort: SortOrder) -> String {
         let mut name = self.0.name.clone();
         name.truncate(16);
-        write!(
-            f,
-            "|{}|{}|{}|",
+        let extra = match sort {
+            SortOrder::Created => self.0.date_created.format("%Y-%m-%d").to_string(),
+            SortOrder::LatestDownloads => crate::search::latest_downloads(self).to_string(),
+            _ => self.0.date_updated.format("%Y-%m-%d").to_string(),
+        };
+
+        format!(
+            "|{}|{}|{}|{}|",
             name.pad_to_width_with_alignment(18, pad::Alignment::Middle),
             self.0
                 .total_downloads()
@@ -201,11 +281,18 @@ impl std::fmt::Display for SearchablePackage {
             self.0
                 .rating_score
                 .to_string()
-                .pad_to_width_with_alignment(14, pad::Alignment::Middle)
+                .pad_to_width_with_alignment(14, pad::Alignment::Middle),
+            extra.pad_to_width_with_alignment(16, pad::Alignment::Middle)
         )
     }
 }
 
+impl std::fmt::Display for SearchablePackage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.table_row(SortOrder::default()))
+    }
+}
+
 impl From<PackageV1> for SearchablePackage {
     fn from(value: PackageV1) -> Self {
         Self(value)
@@ -0,0 +1,352 @@
+use crate::prelude::*;
+use anyhow::anyhow;
+use colored::Colorize;
+use pad::{Alignment, PadStr};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::PathBuf,
+};
+
+/// The profile every game starts with. Its files keep the names used before profiles existed.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Behaviour which can be tuned separately for each profile
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ProfileSettings {
+    /// How many previously installed versions of each package are kept around for rollbacks
+    #[serde(default = "default_kept_versions")]
+    pub kept_versions: usize,
+    /// Whether a snapshot is taken before every update
+    #[serde(default = "default_auto_snapshot")]
+    pub auto_snapshot: bool,
+    /// How many months a mod can go without an update before the health report flags it. `0`
+    /// turns the check off
+    #[serde(default = "default_stale_after_months")]
+    pub stale_after_months: u32,
+}
+
+fn default_kept_versions() -> usize {
+    3
+}
+
+fn default_auto_snapshot() -> bool {
+    true
+}
+
+fn default_stale_after_months() -> u32 {
+    12
+}
+
+impl Default for ProfileSettings {
+    fn default() -> Self {
+        Self {
+            kept_versions: default_kept_versions(),
+            auto_snapshot: default_auto_snapshot(),
+            stale_after_months: default_stale_after_months(),
+        }
+    }
+}
+
+/// A named set of mods for a game, deployed to its own mods directory
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct Profile {
+    /// Where this profile's mods are deployed. When not set, `--mods-dir` must be provided
+    pub mods_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub settings: ProfileSettings,
+}
+
+/// Every profile for a single game, along with which one is currently active
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Profiles {
+    pub active: String,
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Default for Profiles {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_PROFILE.into(),
+            profiles: BTreeMap::from([(DEFAULT_PROFILE.into(), Profile::default())]),
+        }
+    }
+}
+
+impl Profiles {
+    fn path(managed_game: &str) -> PathBuf {
+        CONFIG_DIR.join(format!("profiles_{}.json", managed_game))
+    }
+
+    /// Loads the profiles for a game. Games which have never had a profile created only have the
+    /// default profile.
+    pub fn load(managed_game: &str) -> anyhow::Result<Self> {
+        let path = Self::path(managed_game);
+        if !std::fs::exists(&path)? {
+            return Ok(Self::default());
+        }
+
+        let mut profiles: Self = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        profiles.profiles.entry(DEFAULT_PROFILE.into()).or_default();
+        Ok(profiles)
+    }
+
+    pub fn save(&self, managed_game: &str) -> anyhow::Result<()> {
+        std::fs::create_dir_all(CONFIG_DIR.as_path())?;
+        let writer = BufWriter::new(File::create(Self::path(managed_game))?);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> anyhow::Result<&Profile> {
+        self.profiles
+            .get(name)
+            .ok_or(anyhow!("No profile named {} exists", name))
+    }
+
+    fn get_mut(&mut self, name: &str) -> anyhow::Result<&mut Profile> {
+        self.profiles
+            .get_mut(name)
+            .ok_or(anyhow!("No profile named {} exists", name))
+    }
+
+    fn ensure_free(&self, name: &str) -> anyhow::Result<()> {
+        if self.profiles.contains_key(name) {
+            return Err(anyhow!("A profile named {} already exists", name));
+        }
+        if name.is_empty() || name.contains(['/', '\\', '.']) {
+            return Err(anyhow!("{} is not a valid profile name", name));
+        }
+        Ok(())
+    }
+}
+
+/// The folder holding a non-default profile's requirements and lock files
+pub fn profile_dir(managed_game: &str, profile: &str) -> PathBuf {
+    CONFIG_DIR.join("profiles").join(managed_game).join(profile)
+}
+
+/// The folder holding a profile's snapshots
+pub fn snapshots_dir(managed_game: &str, profile: &str) -> PathBuf {
+    CACHE_DIR.join("snapshots").join(managed_game).join(profile)
+}
+
+pub fn perform_create(
+    managed_game: &str,
+    name: String,
+    mods_dir: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let mut profiles = Profiles::load(managed_game)?;
+    profiles.ensure_free(&name)?;
+
+    let mods_dir = mods_dir.map(std::path::absolute).transpose()?;
+    profiles.profiles.insert(
+        name.clone(),
+        Profile {
+            mods_dir,
+            settings: ProfileSettings::default(),
+        },
+    );
+    profiles.save(managed_game)?;
+
+    println!("Created profile {}", name);
+    Ok(())
+}
+
+/// Creates a new profile with the same requirements and settings as an existing one. Nothing is
+/// installed into the new profile's mods directory until it is updated.
+pub fn perform_clone(
+    managed_game: &str,
+    from: &str,
+    to: String,
+    mods_dir: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let mut profiles = Profiles::load(managed_game)?;
+    profiles.ensure_free(&to)?;
+    let source = profiles.get(from)?.clone();
+
+    let requirements = crate::ProgramState::config_path(managed_game, from);
+    if std::fs::exists(&requirements)? {
+        // Keep the format of the source profile's requirements file
+        let destination = crate::ProgramState::config_path(managed_game, &to)
+            .with_extension(requirements.extension().unwrap_or_default());
+        if let Some(parent) = destination.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(requirements, destination)?;
+    }
+
+    profiles.profiles.insert(
+        to.clone(),
+        Profile {
+            mods_dir: mods_dir.map(std::path::absolute).transpose()?,
+            settings: source.settings,
+        },
+    );
+    profiles.save(managed_game)?;
+
+    println!(
+        "Cloned {} into {}. Run an update with this profile to install its mods",
+        from, to
+    );
+    Ok(())
+}
+
+pub fn perform_rename(managed_game: &str, from: &str, to: String) -> anyhow::Result<()> {
+    if from == DEFAULT_PROFILE {
+        return Err(anyhow!("The {} profile cannot be renamed", DEFAULT_PROFILE));
+    }
+
+    let mut profiles = Profiles::load(managed_game)?;
+    profiles.ensure_free(&to)?;
+    let profile = profiles
+        .profiles
+        .remove(from)
+        .ok_or(anyhow!("No profile named {} exists", from))?;
+
+    for (old_dir, new_dir) in [
+        (
+            profile_dir(managed_game, from),
+            profile_dir(managed_game, &to),
+        ),
+        (
+            snapshots_dir(managed_game, from),
+            snapshots_dir(managed_game, &to),
+        ),
+    ] {
+        if std::fs::exists(&old_dir)? {
+            std::fs::rename(old_dir, new_dir)?;
+        }
+    }
+
+    if profiles.active == from {
+        profiles.active = to.clone();
+    }
+    profiles.profiles.insert(to.clone(), profile);
+    profiles.save(managed_game)?;
+
+    println!("Renamed {} to {}", from, to);
+    Ok(())
+}
+
+/// Deletes a profile's requirements, lock file and snapshots. Its mods directory is left alone.
+pub fn perform_delete(managed_game: &str, name: &str) -> anyhow::Result<()> {
+    let mut profiles = Profiles::load(managed_game)?;
+    if name == DEFAULT_PROFILE {
+        return Err(anyhow!("The {} profile cannot be deleted", DEFAULT_PROFILE));
+    }
+    if profiles.active == name {
+        return Err(anyhow!(
+            "{} is the active profile. Switch to another profile before deleting it",
+            name
+        ));
+    }
+
+    let profile = profiles
+        .profiles
+        .remove(name)
+        .ok_or(anyhow!("No profile named {} exists", name))?;
+
+    for dir in [
+        profile_dir(managed_game, name),
+        snapshots_dir(managed_game, name),
+    ] {
+        if std::fs::exists(&dir)? {
+            std::fs::remove_dir_all(dir)?;
+        }
+    }
+    profiles.save(managed_game)?;
+
+    println!("Deleted profile {}", name);
+    if let Some(mods_dir) = profile.mods_dir {
+        println!("The mods directory {} was not removed", mods_dir.display());
+    }
+    Ok(())
+}
+
+pub fn perform_switch(managed_game: &str, name: &str) -> anyhow::Result<()> {
+    let mut profiles = Profiles::load(managed_game)?;
+    profiles.get(name)?;
+    profiles.active = name.to_owned();
+    profiles.save(managed_game)?;
+
+    println!("Switched to profile {}", name);
+    Ok(())
+}
+
+pub fn perform_list(managed_game: &str) -> anyhow::Result<()> {
+    let profiles = Profiles::load(managed_game)?;
+
+    for (name, profile) in &profiles.profiles {
+        let marker = if *name == profiles.active { "*" } else { " " };
+        let mods_dir = profile
+            .mods_dir
+            .as_ref()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or("(from --mods-dir)".into());
+
+        let line = format!(
+            "{} {}{}",
+            marker,
+            name.pad_to_width_with_alignment(20, Alignment::Left),
+            mods_dir
+        );
+        if *name == profiles.active {
+            println!("{}", line.green());
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    Ok(())
+}
+
+/// Updates the given settings of a profile and prints the result
+pub fn perform_settings(
+    managed_game: &str,
+    name: &str,
+    mods_dir: Option<PathBuf>,
+    kept_versions: Option<usize>,
+    auto_snapshot: Option<bool>,
+    stale_after_months: Option<u32>,
+) -> anyhow::Result<()> {
+    let mut profiles = Profiles::load(managed_game)?;
+    let profile = profiles.get_mut(name)?;
+
+    if let Some(mods_dir) = mods_dir {
+        profile.mods_dir = Some(std::path::absolute(mods_dir)?);
+    }
+    if let Some(kept_versions) = kept_versions {
+        profile.settings.kept_versions = kept_versions;
+    }
+    if let Some(auto_snapshot) = auto_snapshot {
+        profile.settings.auto_snapshot = auto_snapshot;
+    }
+    if let Some(stale_after_months) = stale_after_months {
+        profile.settings.stale_after_months = stale_after_months;
+    }
+
+    let profile = profile.clone();
+    profiles.save(managed_game)?;
+
+    println!("Profile: {}", name);
+    println!(
+        "Mods directory: {}",
+        profile
+            .mods_dir
+            .map(|dir| dir.display().to_string())
+            .unwrap_or("(from --mods-dir)".into())
+    );
+    println!("Kept versions: {}", profile.settings.kept_versions);
+    println!(
+        "Snapshot before updates: {}",
+        profile.settings.auto_snapshot
+    );
+    println!(
+        "Flag mods not updated in: {} months",
+        profile.settings.stale_after_months
+    );
+    Ok(())
+}
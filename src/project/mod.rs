@@ -0,0 +1,119 @@
+use crate::{prelude::*, profile::ProfileSettings};
+use anyhow::anyhow;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, Table};
+
+/// The name of the manifest looked for in the current directory and its parents
+pub const MANIFEST_NAME: &str = "thundermods.toml";
+
+/// The name of the lock file written next to the manifest
+const LOCK_NAME: &str = "thundermods.lock";
+
+/// The profile name shown for sessions loaded from a project manifest
+pub const PROJECT_PROFILE: &str = "project";
+
+/// A `thundermods.toml` kept alongside a server's files. It takes the place of a profile, so the
+/// mod list can be versioned together with the server it is deployed to.
+#[derive(Deserialize, Debug)]
+pub struct ProjectManifest {
+    /// The schema of the manifest. Manifests written before it was versioned are read as 0, and
+    /// have the same layout as version 1
+    #[serde(default)]
+    pub schema_version: u32,
+    /// The game the project's mods are for
+    pub game: String,
+    /// Where the project's mods are deployed, relative to the folder holding the manifest
+    pub mods_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub options: ProfileSettings,
+    #[serde(default)]
+    pub requirements: Requirements,
+}
+
+/// A project manifest along with the location it was loaded from
+#[derive(Debug)]
+pub struct Project {
+    /// The path of the `thundermods.toml` file
+    pub path: PathBuf,
+    pub manifest: ProjectManifest,
+}
+
+impl Project {
+    /// Looks for a manifest in the current directory, then in each of its parents
+    pub fn find() -> anyhow::Result<Option<Self>> {
+        let current_dir = std::env::current_dir()?;
+        for dir in current_dir.ancestors() {
+            let path = dir.join(MANIFEST_NAME);
+            if std::fs::exists(&path)? {
+                return Self::load(path).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    fn load(path: PathBuf) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(&path)?;
+        let manifest: ProjectManifest = toml_edit::de::from_str(&contents)
+            .map_err(|err| anyhow!("Unable to read {}: {}", path.display(), err))?;
+        crate::requirements::check_schema(
+            &path,
+            manifest.schema_version,
+            crate::requirements::CURRENT_SCHEMA,
+        )?;
+        Ok(Self { path, manifest })
+    }
+
+    /// The folder holding the manifest, which relative paths in the manifest are resolved against
+    pub fn root(&self) -> &Path {
+        self.path.parent().unwrap_or(Path::new("."))
+    }
+
+    /// The mods directory configured in the manifest, resolved against the project folder
+    pub fn mods_dir(&self) -> Option<PathBuf> {
+        self.manifest
+            .mods_dir
+            .as_ref()
+            .map(|mods_dir| self.root().join(mods_dir))
+    }
+
+    pub fn lock_path(&self) -> PathBuf {
+        lock_path(&self.path)
+    }
+}
+
+/// The lock file kept next to a project manifest
+pub fn lock_path(manifest_path: &Path) -> PathBuf {
+    manifest_path.with_file_name(LOCK_NAME)
+}
+
+/// The name of the folder holding a project's snapshots. Projects are told apart by the location
+/// of their manifest.
+pub fn snapshots_name(manifest_path: &Path) -> String {
+    let hash = crate::utils::hash_bytes(manifest_path.as_os_str().as_encoded_bytes());
+    format!("project-{}", &hash[..16])
+}
+
+/// Writes the requirements into the `[requirements]` table of a manifest, keeping the comments and
+/// formatting of everything else
+pub fn save_requirements(manifest_path: &Path, requirements: &Requirements) -> anyhow::Result<()> {
+    let mut document = std::fs::read_to_string(manifest_path)?.parse::<DocumentMut>()?;
+    if !document.contains_key("schema_version") {
+        document.insert(
+            "schema_version",
+            toml_edit::value(crate::requirements::CURRENT_SCHEMA as i64),
+        );
+    }
+    let table = document
+        .entry("requirements")
+        .or_insert_with(|| Item::Table(Table::new()))
+        .as_table_mut()
+        .ok_or(anyhow!(
+            "requirements in {} must be a table",
+            manifest_path.display()
+        ))?;
+    crate::requirements::update_toml_table(table, requirements)?;
+
+    std::fs::write(manifest_path, document.to_string())?;
+    Ok(())
+}
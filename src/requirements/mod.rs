@@ -0,0 +1,349 @@
+mod yaml;
+
+use crate::prelude::*;
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+use toml_edit::{DocumentMut, Item, Table, de::ValueDeserializer, ser::ValueSerializer};
+
+/// The formats a requirements file can be written in, chosen by the file's extension
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl Format {
+    /// Every format along with its extensions, in the order they are looked for
+    const EXTENSIONS: [(&str, Format); 4] = [
+        ("toml", Format::Toml),
+        ("yaml", Format::Yaml),
+        ("yml", Format::Yaml),
+        ("json", Format::Json),
+    ];
+
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        Self::EXTENSIONS
+            .into_iter()
+            .find(|(known, _)| Some(*known) == extension)
+            .map(|(_, format)| format)
+            .unwrap_or(Format::Json)
+    }
+}
+
+/// Finds the requirements file named `stem` in `dir`, whichever format it is written in. When no
+/// file exists yet, the JSON file is returned.
+pub fn find(dir: &Path, stem: &str) -> PathBuf {
+    Format::EXTENSIONS
+        .into_iter()
+        .map(|(extension, _)| dir.join(format!("{}.{}", stem, extension)))
+        .find(|path| path.exists())
+        .unwrap_or(dir.join(format!("{}.json", stem)))
+}
+
+/// The paths a requirements file would have if it was written in one of the other formats
+pub fn other_formats(path: &Path) -> Vec<PathBuf> {
+    let extension = path.extension().and_then(|extension| extension.to_str());
+    Format::EXTENSIONS
+        .into_iter()
+        .filter(|(other, _)| Some(*other) != extension)
+        .map(|(other, _)| path.with_extension(other))
+        .collect()
+}
+
+/// The schema version written to new requirements files
+pub const CURRENT_SCHEMA: u32 = 1;
+
+/// The layout of a requirements file. Files written before the schema was versioned hold nothing
+/// but the map of requirements, and are treated as schema version 0.
+#[derive(Deserialize, Serialize)]
+struct RequirementsFile {
+    schema_version: u32,
+    #[serde(default)]
+    requirements: Requirements,
+}
+
+/// Just enough of a requirements file to tell which schema it was written with
+#[derive(Deserialize)]
+struct SchemaProbe {
+    schema_version: Option<u32>,
+}
+
+fn parse<T: DeserializeOwned>(format: Format, contents: &str) -> anyhow::Result<T> {
+    Ok(match format {
+        Format::Json => serde_json::from_str(contents)?,
+        Format::Toml => toml_edit::de::from_str(contents)?,
+        Format::Yaml => serde_yaml_ng::from_str(contents)?,
+    })
+}
+
+/// The error for a file written with a newer schema than this program understands
+pub fn check_schema(path: &Path, schema_version: u32, supported: u32) -> anyhow::Result<()> {
+    if schema_version <= supported {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "{} uses schema version {}, but only versions up to {} are supported. It was likely \
+         written by a newer version of this program",
+        path.display(),
+        schema_version,
+        supported
+    ))
+}
+
+/// The schema a requirements file was written with, 0 if it predates schema versions
+fn schema_version(format: Format, contents: &str) -> anyhow::Result<u32> {
+    Ok(parse::<SchemaProbe>(format, contents)?
+        .schema_version
+        .unwrap_or(0))
+}
+
+/// Reads a requirements file, whichever schema it was written with. Older files are left as they
+/// are until the requirements are next written. A file which doesn't exist holds no requirements.
+pub fn read(path: &Path) -> anyhow::Result<Requirements> {
+    if !std::fs::exists(path)? {
+        return Ok(Requirements::default());
+    }
+
+    let contents = std::fs::read_to_string(path)?;
+    let format = Format::from_path(path);
+    let read_error = |err: anyhow::Error| anyhow!("Unable to read {}: {}", path.display(), err);
+
+    let schema_version = schema_version(format, &contents).map_err(read_error)?;
+    check_schema(path, schema_version, CURRENT_SCHEMA)?;
+    if schema_version == 0 {
+        return parse::<Requirements>(format, &contents).map_err(read_error);
+    }
+    Ok(parse::<RequirementsFile>(format, &contents)
+        .map_err(read_error)?
+        .requirements)
+}
+
+/// Moves the entries of an unversioned requirements file under a `requirements` key, next to the
+/// schema version. Comments are carried along with the entries they belong to. JSON files are
+/// rewritten from scratch, so they are returned as they are.
+fn migrate(format: Format, contents: &str) -> anyhow::Result<String> {
+    Ok(match format {
+        Format::Json => contents.to_owned(),
+        Format::Toml => {
+            let mut document = contents.parse::<DocumentMut>()?;
+            let root = document.as_table_mut();
+            let keys = root
+                .iter()
+                .map(|(key, _)| key.to_owned())
+                .filter(|key| NamespacedPackage::value_parser(key).is_ok())
+                .collect::<Vec<_>>();
+
+            let mut table = Table::new();
+            for key in keys {
+                if let Some((key, item)) = root.remove_entry(&key) {
+                    table.insert_formatted(&key, item);
+                }
+            }
+
+            root.insert("schema_version", toml_edit::value(CURRENT_SCHEMA as i64));
+            root.insert("requirements", Item::Table(table));
+            document.to_string()
+        }
+        Format::Yaml => yaml::migrate(contents),
+    })
+}
+
+/// Writes the requirements in the format of the file's extension. TOML and YAML files are edited
+/// in place, so comments and formatting around entries which did not change are kept.
+pub fn write(path: &Path, requirements: &Requirements) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let format = Format::from_path(path);
+    let existing = std::fs::read_to_string(path)
+        .ok()
+        .map(|contents| -> anyhow::Result<String> {
+            // Files written before the schema was versioned are brought up to date by the first
+            // change made to them
+            if !contents.trim().is_empty() && schema_version(format, &contents)? == 0 {
+                return migrate(format, &contents);
+            }
+            Ok(contents)
+        })
+        .transpose()
+        .map_err(|err| anyhow!("Unable to read {}: {}", path.display(), err))?;
+
+    match format {
+        Format::Json => {
+            let writer = BufWriter::new(File::create(path)?);
+            serde_json::to_writer_pretty(
+                writer,
+                &RequirementsFile {
+                    schema_version: CURRENT_SCHEMA,
+                    requirements: requirements.clone(),
+                },
+            )?;
+        }
+        Format::Toml => {
+            let mut document = existing.unwrap_or_default().parse::<DocumentMut>()?;
+            let root = document.as_table_mut();
+            if !root.contains_key("schema_version") {
+                root.insert("schema_version", toml_edit::value(CURRENT_SCHEMA as i64));
+            }
+            let table = root
+                .entry("requirements")
+                .or_insert_with(|| Item::Table(Table::new()))
+                .as_table_mut()
+                .ok_or(anyhow!(
+                    "requirements in {} must be a table",
+                    path.display()
+                ))?;
+            update_toml_table(table, requirements)?;
+            std::fs::write(path, document.to_string())?;
+        }
+        Format::Yaml => {
+            let contents = yaml::update(&existing.unwrap_or_default(), requirements)?;
+            std::fs::write(path, contents)?;
+        }
+    }
+    Ok(())
+}
+
+fn parse_toml_requirement(item: &Item) -> Option<Requirement> {
+    let mut value = item.clone().into_value().ok()?;
+    value.decor_mut().clear();
+    let deserializer = value.to_string().parse::<ValueDeserializer>().ok()?;
+    Requirement::deserialize(deserializer).ok()
+}
+
+/// Makes a TOML table hold exactly the given requirements. Entries which have not changed are left
+/// untouched, so any comments and formatting around them are kept.
+pub fn update_toml_table(table: &mut Table, requirements: &Requirements) -> anyhow::Result<()> {
+    table.retain(|key, _| {
+        NamespacedPackage::value_parser(key)
+            .is_ok_and(|namespaced| requirements.get(&namespaced).is_some())
+    });
+
+    let mut sorted = requirements.iter().collect::<Vec<_>>();
+    sorted.sort_by_key(|(namespaced, _)| *namespaced);
+    for (namespaced, requirement) in sorted {
+        let key = namespaced.to_string();
+        if table
+            .get(&key)
+            .and_then(parse_toml_requirement)
+            .is_some_and(|existing| existing == *requirement)
+        {
+            continue;
+        }
+
+        let mut value = serde::Serialize::serialize(requirement, ValueSerializer::new())?;
+        match table.get_mut(&key).and_then(Item::as_value_mut) {
+            Some(existing) => {
+                // Keep any trailing comment on the entry being replaced
+                *value.decor_mut() = existing.decor().clone();
+                *existing = value;
+            }
+            None => {
+                table.insert(&key, Item::Value(value));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestDir;
+
+    /// Writes an unversioned file, checks that reading it leaves it alone, then writes it back and
+    /// returns what was written
+    fn migrate_on_write(file_name: &str, contents: &str) -> String {
+        let dir = TestDir::new(file_name);
+        let path = dir.join(file_name);
+        std::fs::write(&path, contents).unwrap();
+
+        let mut requirements = read(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), contents);
+        assert_eq!(requirements.iter().count(), 1);
+
+        requirements.insert(
+            NamespacedPackage::value_parser("C/D").unwrap(),
+            "^2.0.0".parse().unwrap(),
+        );
+        write(&path, &requirements).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+
+        assert_eq!(
+            schema_version(Format::from_path(&path), &written).unwrap(),
+            1
+        );
+        written
+    }
+
+    #[test]
+    fn unversioned_json_is_migrated_on_write() {
+        let written = migrate_on_write("requirements.json", r#"{ "A/B": "^1.0.0" }"#);
+
+        let file: RequirementsFile = serde_json::from_str(&written).unwrap();
+        assert_eq!(file.requirements.iter().count(), 2);
+    }
+
+    #[test]
+    fn unversioned_toml_is_migrated_on_write() {
+        let written = migrate_on_write(
+            "requirements.toml",
+            "\
+# Server mods
+\"A/B\" = \"^1.0.0\" # pinned
+",
+        );
+
+        assert_eq!(
+            written,
+            "\
+schema_version = 1
+
+[requirements]
+# Server mods
+\"A/B\" = \"^1.0.0\" # pinned
+\"C/D\" = \"^2.0.0\"
+"
+        );
+    }
+
+    #[test]
+    fn unversioned_yaml_is_migrated_on_write() {
+        let written = migrate_on_write(
+            "requirements.yaml",
+            "\
+# Server mods
+A/B: ^1.0.0 # pinned
+",
+        );
+
+        assert_eq!(
+            written,
+            "\
+# Server mods
+schema_version: 1
+requirements:
+  A/B: ^1.0.0 # pinned
+  C/D: ^2.0.0
+"
+        );
+    }
+
+    #[test]
+    fn newer_schemas_are_refused() {
+        let dir = TestDir::new("newer_schemas_are_refused");
+        let path = dir.join("requirements.json");
+        std::fs::write(&path, r#"{ "schema_version": 2, "requirements": {} }"#).unwrap();
+
+        let result = read(&path);
+        assert!(result.unwrap_err().to_string().contains("schema version 2"));
+    }
+}
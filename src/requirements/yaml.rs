@@ -0,0 +1,621 @@
+use crate::prelude::*;
+use std::collections::BTreeMap;
+
+/// The lines making up a single entry of a block mapping
+struct Entry {
+    key: String,
+    /// Index of the line holding the key
+    start: usize,
+    /// Index one past the last line belonging to the entry
+    end: usize,
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// Lines which don't hold any content of their own
+fn is_filler(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty() || trimmed.starts_with('#') || trimmed == "---" || trimmed == "..."
+}
+
+/// Reads the key at the start of a mapping entry, without any quotes around it
+fn parse_key(line: &str) -> Option<String> {
+    let line = line.trim_start();
+    match line.chars().next()? {
+        quote @ ('"' | '\'') => {
+            let close = line[1..].find(quote)?;
+            Some(line[1..=close].to_owned())
+        }
+        '-' | '{' | '[' => None,
+        _ => {
+            let (key, _) = line.split_once(':')?;
+            Some(key.trim_end().to_owned())
+        }
+    }
+}
+
+/// Finds the comment at the end of a line, ignoring any `#` within quoted strings. A quote only
+/// opens a string at the start of a key or value, so the apostrophe in `note: don't` doesn't.
+fn trailing_comment(line: &str) -> Option<&str> {
+    let mut quote = None;
+    let mut previous = ' ';
+    // The last character which wasn't whitespace, outside of any string
+    let mut last_token = None;
+    let mut chars = line.char_indices().peekable();
+    while let Some((index, char)) = chars.next() {
+        match (quote, char) {
+            (Some('"'), '\\') => {
+                chars.next();
+            }
+            // Quotes are escaped by doubling them in single quoted strings
+            (Some('\''), '\'') if chars.peek().is_some_and(|(_, next)| *next == '\'') => {
+                chars.next();
+            }
+            (Some(open), _) if open == char => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'')
+                if last_token.is_none_or(|token| matches!(token, ':' | '-' | '[' | '{' | ',')) =>
+            {
+                quote = Some(char);
+            }
+            (None, '#') if previous.is_whitespace() => return Some(&line[index..]),
+            (None, _) if !char.is_whitespace() => last_token = Some(char),
+            (None, _) => {}
+        }
+        previous = char;
+    }
+    None
+}
+
+/// Whether two sets of requirements hold the same entries
+fn same_requirements(a: &Requirements, b: &Requirements) -> bool {
+    a.iter().count() == b.iter().count()
+        && a.iter()
+            .all(|(namespaced, requirement)| b.get(namespaced) == Some(requirement))
+}
+
+/// Finds every entry of the block mapping whose keys sit at `indent`, starting from `from`
+fn entries(lines: &[&str], indent: usize, from: usize) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = Vec::new();
+
+    for (index, line) in lines.iter().enumerate().skip(from) {
+        if is_filler(line) {
+            continue;
+        }
+
+        let line_indent = indent_of(line);
+        if line_indent > indent {
+            if let Some(entry) = entries.last_mut() {
+                entry.end = index + 1;
+            }
+            continue;
+        }
+        if line_indent < indent {
+            break;
+        }
+
+        if let Some(key) = parse_key(line) {
+            entries.push(Entry {
+                key,
+                start: index,
+                end: index + 1,
+            });
+        }
+    }
+
+    entries
+}
+
+/// Renders a single entry at the given indentation
+fn render(
+    namespaced: &NamespacedPackage,
+    requirement: &Requirement,
+    indent: usize,
+    comment: Option<&str>,
+) -> anyhow::Result<Vec<String>> {
+    let rendered = serde_yaml_ng::to_string(&BTreeMap::from([(namespaced, requirement)]))?;
+    let mut lines = rendered
+        .lines()
+        .map(|line| format!("{}{}", " ".repeat(indent), line))
+        .collect::<Vec<_>>();
+
+    if let (Some(comment), Some(first)) = (comment, lines.first_mut()) {
+        first.push(' ');
+        first.push_str(comment);
+    }
+    Ok(lines)
+}
+
+fn parse_entry(lines: &[&str], entry: &Entry, indent: usize) -> Option<Requirement> {
+    let text = lines[entry.start..entry.end]
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or(line.trim_start()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut parsed = serde_yaml_ng::from_str::<BTreeMap<String, Requirement>>(&text).ok()?;
+    parsed.remove(&entry.key)
+}
+
+/// Rewrites the block mapping starting at line `from`, whose keys sit at `indent`, so that it
+/// holds exactly the given requirements. YAML parsers throw comments away, so entries are located
+/// by their indentation instead and only the lines of entries which changed are rewritten.
+fn update_mapping(
+    contents: &str,
+    requirements: &Requirements,
+    indent: usize,
+    from: usize,
+) -> anyhow::Result<String> {
+    let lines = contents.lines().collect::<Vec<_>>();
+    let entries = entries(&lines, indent, from);
+
+    let mut replacements: BTreeMap<usize, (usize, Vec<String>)> = BTreeMap::new();
+    let mut seen = Vec::new();
+    for entry in &entries {
+        let Ok(namespaced) = NamespacedPackage::value_parser(&entry.key) else {
+            continue;
+        };
+
+        match requirements.get(&namespaced) {
+            None => {
+                // Comments directly above an entry describe it, so they go along with it
+                let start = (from..entry.start)
+                    .rev()
+                    .take_while(|index| lines[*index].trim_start().starts_with('#'))
+                    .last()
+                    .unwrap_or(entry.start);
+                replacements.insert(start, (entry.end, Vec::new()));
+            }
+            Some(requirement) => {
+                if parse_entry(&lines, entry, indent).as_ref() != Some(requirement) {
+                    let comment = trailing_comment(lines[entry.start]);
+                    let rendered = render(&namespaced, requirement, indent, comment)?;
+                    replacements.insert(entry.start, (entry.end, rendered));
+                }
+            }
+        }
+        seen.push(namespaced);
+    }
+
+    let mut added = requirements
+        .iter()
+        .filter(|(namespaced, _)| !seen.contains(namespaced))
+        .collect::<Vec<_>>();
+    added.sort_by_key(|(namespaced, _)| *namespaced);
+    let mut added_lines = Vec::new();
+    for (namespaced, requirement) in added {
+        added_lines.extend(render(namespaced, requirement, indent, None)?);
+    }
+
+    let insert_at = entries.last().map(|last| last.end).unwrap_or(from);
+
+    let mut output = Vec::new();
+    let mut index = 0;
+    while index <= lines.len() {
+        if index == insert_at {
+            output.append(&mut added_lines);
+        }
+        if index == lines.len() {
+            break;
+        }
+        match replacements.remove(&index) {
+            Some((end, rendered)) => {
+                output.extend(rendered);
+                index = end;
+            }
+            None => {
+                output.push(lines[index].to_owned());
+                index += 1;
+            }
+        }
+    }
+
+    let mut output = output.join("\n");
+    output.push('\n');
+    Ok(output)
+}
+
+/// Rewrites a requirements file so that its `requirements` mapping holds exactly the given
+/// requirements, adding the mapping and schema version if they are missing. Only block mappings
+/// can be edited line by line, so files written in another layout, such as a flow mapping, are
+/// written out again from scratch without their comments.
+pub fn update(contents: &str, requirements: &Requirements) -> anyhow::Result<String> {
+    let edited = edit(contents, requirements)?;
+    let edited_correctly = serde_yaml_ng::from_str::<super::RequirementsFile>(&edited)
+        .is_ok_and(|file| same_requirements(&file.requirements, requirements));
+    if edited_correctly {
+        return Ok(edited);
+    }
+
+    Ok(serde_yaml_ng::to_string(&super::RequirementsFile {
+        schema_version: super::CURRENT_SCHEMA,
+        requirements: requirements.clone(),
+    })?)
+}
+
+/// Edits the lines of a requirements file written as block mappings
+fn edit(contents: &str, requirements: &Requirements) -> anyhow::Result<String> {
+    let mut lines = contents.lines().map(str::to_owned).collect::<Vec<_>>();
+    let top_level_key = |line: &String| (indent_of(line) == 0).then(|| parse_key(line)).flatten();
+
+    // A file holding nothing but an empty mapping is written as `{}`, which can't be mixed with
+    // the keys added below
+    lines.retain(|line| {
+        let comment = trailing_comment(line).map_or(0, str::len);
+        line[..line.len() - comment].trim() != "{}"
+    });
+
+    if !lines
+        .iter()
+        .any(|line| top_level_key(line).as_deref() == Some("schema_version"))
+    {
+        lines.insert(0, format!("schema_version: {}", super::CURRENT_SCHEMA));
+    }
+
+    let header = match lines
+        .iter()
+        .position(|line| top_level_key(line).as_deref() == Some("requirements"))
+    {
+        Some(header) => {
+            // An empty mapping is written as `{}`, which has to make way for the entries
+            let line = &lines[header];
+            let comment = trailing_comment(line).map(str::to_owned);
+            let value = line[..line.len() - comment.as_deref().map_or(0, str::len)]
+                .split_once(':')
+                .map(|(_, value)| value.trim())
+                .unwrap_or_default();
+            if value == "{}" {
+                lines[header] = match comment {
+                    Some(comment) => format!("requirements: {}", comment),
+                    None => "requirements:".to_owned(),
+                };
+            }
+            header
+        }
+        None => {
+            lines.push("requirements:".to_owned());
+            lines.len() - 1
+        }
+    };
+
+    let indent = lines
+        .iter()
+        .skip(header + 1)
+        .find(|line| !is_filler(line))
+        .map(|line| indent_of(line))
+        .filter(|indent| *indent > 0)
+        .unwrap_or(2);
+
+    update_mapping(&lines.join("\n"), requirements, indent, header + 1)
+}
+
+/// Moves every entry of an unversioned requirements file under a `requirements` key. Comments at
+/// the top of the file are left where they are, and entries are indented by the same step the file
+/// already uses for nested values.
+pub fn migrate(contents: &str) -> String {
+    let lines = contents.lines().collect::<Vec<_>>();
+    let leading = lines.iter().take_while(|line| is_filler(line)).count();
+
+    let mut output = lines[..leading]
+        .iter()
+        .map(|line| (*line).to_owned())
+        .collect::<Vec<_>>();
+    output.push(format!("schema_version: {}", super::CURRENT_SCHEMA));
+
+    let body = &lines[leading..];
+    if body
+        .iter()
+        .all(|line| is_filler(line) || line.trim() == "{}")
+    {
+        output.push("requirements: {}".to_owned());
+    } else {
+        let step = body
+            .iter()
+            .filter(|line| !is_filler(line))
+            .map(|line| indent_of(line))
+            .filter(|indent| *indent > 0)
+            .min()
+            .unwrap_or(2);
+        output.push("requirements:".to_owned());
+        output.extend(body.iter().map(|line| match line.trim() {
+            "" | "---" | "..." => (*line).to_owned(),
+            _ => format!("{}{}", " ".repeat(step), line),
+        }));
+    }
+
+    let mut output = output.join("\n");
+    output.push('\n');
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn requirements(entries: &[(&str, Requirement)]) -> Requirements {
+        let mut requirements = Requirements::default();
+        for (key, requirement) in entries {
+            let namespaced = NamespacedPackage::value_parser(key).unwrap();
+            requirements.insert(namespaced.clone(), requirement.version.clone());
+            *requirements.get_mut(&namespaced).unwrap() = requirement.clone();
+        }
+        requirements
+    }
+
+    fn bare(version: &str) -> Requirement {
+        Requirement::new(version.parse().unwrap())
+    }
+
+    fn noted(version: &str, note: &str) -> Requirement {
+        Requirement {
+            note: Some(note.to_owned()),
+            ..bare(version)
+        }
+    }
+
+    /// Parses an edited file back, to check that the edit left valid YAML holding the requirements
+    fn read_back(contents: &str) -> Requirements {
+        serde_yaml_ng::from_str::<super::super::RequirementsFile>(contents)
+            .unwrap()
+            .requirements
+    }
+
+    const COMMENTED: &str = "\
+# Server mods
+schema_version: 1
+requirements:
+  # Needed by every map
+  A/B: ^1.0.0 # pinned until the next patch
+  C/D: ^2.0.0
+";
+
+    #[test]
+    fn changing_an_entry_keeps_its_comments() {
+        let updated = update(
+            COMMENTED,
+            &requirements(&[("A/B", bare("^1.1.0")), ("C/D", bare("^2.0.0"))]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            updated,
+            "\
+# Server mods
+schema_version: 1
+requirements:
+  # Needed by every map
+  A/B: ^1.1.0 # pinned until the next patch
+  C/D: ^2.0.0
+"
+        );
+    }
+
+    #[test]
+    fn adding_an_entry_leaves_the_rest_untouched() {
+        let updated = update(
+            COMMENTED,
+            &requirements(&[
+                ("A/B", bare("^1.0.0")),
+                ("C/D", bare("^2.0.0")),
+                ("E/F", bare("^3.0.0")),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(updated, format!("{}  E/F: ^3.0.0\n", COMMENTED));
+    }
+
+    #[test]
+    fn removing_an_entry_removes_the_comments_above_it() {
+        let updated = update(COMMENTED, &requirements(&[("C/D", bare("^2.0.0"))])).unwrap();
+
+        assert_eq!(
+            updated,
+            "\
+# Server mods
+schema_version: 1
+requirements:
+  C/D: ^2.0.0
+"
+        );
+        assert!(
+            read_back(&updated)
+                .get(&NamespacedPackage::new("A", "B"))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn removing_a_multi_line_entry_removes_all_of_its_lines() {
+        let contents = "\
+schema_version: 1
+requirements:
+  A/B:
+    version: ^1.0.0
+    note: for the map # why
+  C/D: ^2.0.0 # kept
+";
+        let updated = update(contents, &requirements(&[("C/D", bare("^2.0.0"))])).unwrap();
+
+        assert_eq!(
+            updated,
+            "\
+schema_version: 1
+requirements:
+  C/D: ^2.0.0 # kept
+"
+        );
+    }
+
+    #[test]
+    fn unchanged_inline_entries_are_kept_as_written() {
+        let contents = "\
+schema_version: 1
+requirements:
+  A/B: { version: ^1.0.0, note: for the map } # inline
+  C/D: ^2.0.0
+";
+        let updated = update(
+            contents,
+            &requirements(&[
+                ("A/B", noted("^1.0.0", "for the map")),
+                ("C/D", bare("^2.1.0")),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            updated,
+            "\
+schema_version: 1
+requirements:
+  A/B: { version: ^1.0.0, note: for the map } # inline
+  C/D: ^2.1.0
+"
+        );
+    }
+
+    #[test]
+    fn changed_inline_entries_keep_their_comment() {
+        let contents = "\
+schema_version: 1
+requirements:
+  A/B: { version: ^1.0.0, note: for the map } # inline
+";
+        let wanted = requirements(&[("A/B", noted("^1.0.0", "for every map"))]);
+        let updated = update(contents, &wanted).unwrap();
+
+        assert!(updated.contains("A/B: # inline\n"), "{}", updated);
+        assert_eq!(
+            read_back(&updated).get(&NamespacedPackage::new("A", "B")),
+            wanted.get(&NamespacedPackage::new("A", "B"))
+        );
+    }
+
+    #[test]
+    fn entries_can_be_added_to_an_empty_file() {
+        let wanted = requirements(&[("A/B", bare("^1.0.0"))]);
+
+        for contents in [
+            "",
+            "{}\n",
+            "schema_version: 1\nrequirements: {} # none yet\n",
+        ] {
+            let updated = update(contents, &wanted).unwrap();
+            assert_eq!(
+                read_back(&updated).get(&NamespacedPackage::new("A", "B")),
+                Some(&bare("^1.0.0")),
+                "{:?} became {:?}",
+                contents,
+                updated
+            );
+        }
+    }
+
+    #[test]
+    fn quoted_keys_are_matched() {
+        let contents = "\
+schema_version: 1
+requirements:
+  \"A/B\": ^1.0.0 # double
+  'C/D': ^2.0.0 # single
+";
+        let updated = update(
+            contents,
+            &requirements(&[("A/B", bare("^1.0.0")), ("C/D", bare("^2.1.0"))]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            updated,
+            "\
+schema_version: 1
+requirements:
+  \"A/B\": ^1.0.0 # double
+  C/D: ^2.1.0 # single
+"
+        );
+    }
+
+    #[test]
+    fn migrating_keeps_comments_and_indentation() {
+        let contents = "\
+# Server mods
+A/B: ^1.0.0 # pinned
+C/D:
+    version: ^2.0.0
+    note: nested
+";
+        let migrated = migrate(contents);
+
+        assert_eq!(
+            migrated,
+            "\
+# Server mods
+schema_version: 1
+requirements:
+    A/B: ^1.0.0 # pinned
+    C/D:
+        version: ^2.0.0
+        note: nested
+"
+        );
+        assert_eq!(read_back(&migrated).iter().count(), 2);
+    }
+
+    #[test]
+    fn comments_set_apart_by_a_blank_line_are_kept() {
+        let contents = "\
+schema_version: 1
+requirements:
+  # Map mods
+
+  # Needed by every map
+  # Don't update without the server
+  A/B: ^1.0.0
+  C/D: ^2.0.0
+";
+        let updated = update(contents, &requirements(&[("C/D", bare("^2.0.0"))])).unwrap();
+
+        assert_eq!(
+            updated,
+            "\
+schema_version: 1
+requirements:
+  # Map mods
+
+  C/D: ^2.0.0
+"
+        );
+    }
+
+    #[test]
+    fn flow_mappings_are_written_out_again() {
+        let wanted = requirements(&[("A/B", bare("^1.0.0")), ("C/D", bare("^2.0.0"))]);
+
+        for contents in [
+            "{schema_version: 1, requirements: {A/B: ^1.0.0}}\n",
+            "schema_version: 1\nrequirements: {A/B: ^1.0.0}\n",
+        ] {
+            let updated = update(contents, &wanted).unwrap();
+            assert!(
+                same_requirements(&read_back(&updated), &wanted),
+                "{}",
+                updated
+            );
+        }
+    }
+
+    #[test]
+    fn apostrophes_in_values_do_not_hide_comments() {
+        assert_eq!(trailing_comment("note: don't # c"), Some("# c"));
+        assert_eq!(trailing_comment("'A/B': ^1.0.0 # c"), Some("# c"));
+        assert_eq!(
+            trailing_comment("note: 'it''s # not a comment' # c"),
+            Some("# c")
+        );
+        assert_eq!(trailing_comment(r#"note: "a \" # b" # c"#), Some("# c"));
+        assert_eq!(trailing_comment("note: \"# not a comment\""), None);
+    }
+}
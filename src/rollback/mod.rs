@@ -0,0 +1,120 @@
+use crate::{ProgramState, prelude::NamespacedPackage};
+use anyhow::anyhow;
+use colored::Colorize;
+use thunderstore::VersionIdent;
+
+/// Restores a package to an earlier version, or removes it entirely, along with its requirement, if
+/// it was not installed before. Required mods are pinned to the restored version so the next update
+/// does not undo the rollback.
+async fn rollback_package(
+    program_state: &mut ProgramState,
+    api: &thunderstore::Client,
+    namespaced: &NamespacedPackage,
+    target: Option<semver::Version>,
+) -> anyhow::Result<()> {
+    let Some(target) = target else {
+        let install_dir = program_state.install_dir(namespaced);
+        if std::fs::exists(&install_dir)? {
+            std::fs::remove_dir_all(install_dir)?;
+        }
+        program_state.lockfile.remove(namespaced);
+        program_state.save_lockfile()?;
+
+        // Otherwise the next update would install it all over again
+        if program_state.requirements.remove(namespaced).is_some() {
+            program_state.save_requirements()?;
+            println!("{}: {}", namespaced, "removed from the requirements".red());
+        } else {
+            println!("{}: {}", namespaced, "removed".red());
+        }
+        return Ok(());
+    };
+
+    let current = program_state
+        .lockfile
+        .get(namespaced)
+        .ok_or(anyhow!("{} is not installed", namespaced))?
+        .clone();
+
+    let ident = VersionIdent::new(
+        namespaced.namespace(),
+        namespaced.name(),
+        target.to_string(),
+    );
+    crate::install::install_version(program_state, api, &ident).await?;
+
+    // Installing pushes the rolled back version onto the history, which should be forgotten instead
+    if let Some(locked) = program_state.lockfile.get_mut(namespaced) {
+        locked.previous = current
+            .previous
+            .into_iter()
+            .filter(|previous| *previous != target)
+            .collect();
+    }
+    program_state.save_lockfile()?;
+
+    if program_state.requirements.get(namespaced).is_some() {
+        program_state.requirements.insert(
+            namespaced.clone(),
+            semver::VersionReq::parse(&format!("={}", target))?,
+        );
+        program_state.save_requirements()?;
+    }
+
+    println!(
+        "{}: {} -> {}",
+        namespaced,
+        current.version,
+        target.to_string().green()
+    );
+    Ok(())
+}
+
+/// Rolls a single mod back to the version installed before its current one, or when no mod is
+/// given, reverts every package changed by the last update.
+pub async fn perform_rollback(
+    program_state: &mut ProgramState,
+    api: &thunderstore::Client,
+    mod_name: Option<NamespacedPackage>,
+) -> anyhow::Result<()> {
+    if let Some(mod_name) = mod_name {
+        let previous = program_state
+            .lockfile
+            .get(&mod_name)
+            .ok_or(anyhow!("{} is not installed", mod_name))?
+            .previous
+            .first()
+            .cloned()
+            .ok_or(anyhow!(
+                "There is no previous version of {} to roll back to",
+                mod_name
+            ))?;
+
+        rollback_package(program_state, api, &mod_name, Some(previous)).await?;
+
+        program_state
+            .lockfile
+            .last_operation
+            .retain(|(namespaced, _)| *namespaced != mod_name);
+        program_state.save_lockfile()?;
+        return Ok(());
+    }
+
+    let last_operation = program_state.lockfile.last_operation.clone();
+    if last_operation.is_empty() {
+        return Err(anyhow!("There is no update to roll back"));
+    }
+
+    // Each package is forgotten only once it is rolled back, so a failure can be retried
+    for (namespaced, previous) in last_operation {
+        rollback_package(program_state, api, &namespaced, previous).await?;
+
+        program_state
+            .lockfile
+            .last_operation
+            .retain(|(changed, _)| *changed != namespaced);
+        program_state.save_lockfile()?;
+    }
+
+    Ok(())
+}
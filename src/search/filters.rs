@@ -0,0 +1,353 @@
+use super::SortOrder;
+use crate::prelude::*;
+use anyhow::anyhow;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashSet},
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::PathBuf,
+};
+
+/// Narrows down which packages are shown in search results. Every filter which is set has to
+/// match for a package to be shown.
+#[derive(clap::Args, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct SearchFilters {
+    /// Only show packages in at least one of these categories
+    #[arg(long = "category", value_name = "CATEGORY")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include_categories: Vec<String>,
+    /// Hide packages in any of these categories
+    #[arg(long = "exclude-category", value_name = "CATEGORY")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_categories: Vec<String>,
+    /// Only show mods which run on the server
+    #[arg(long)]
+    #[serde(default)]
+    pub server_side: bool,
+    /// Hide packages with NSFW content
+    #[arg(long)]
+    #[serde(default)]
+    pub hide_nsfw: bool,
+    /// Hide deprecated packages
+    #[arg(long)]
+    #[serde(default)]
+    pub hide_deprecated: bool,
+    /// Hide packages which are already installed
+    #[arg(long)]
+    #[serde(default)]
+    pub hide_installed: bool,
+    /// Only show packages rated at least this highly
+    #[arg(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_rating: Option<u32>,
+    /// Only show packages updated on or after this date, formatted as YYYY-MM-DD
+    #[arg(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_after: Option<NaiveDate>,
+}
+
+/// The filters and sort order last used when searching for mods of a game
+#[derive(Deserialize, Serialize, Default)]
+pub struct SavedSearch {
+    #[serde(flatten)]
+    pub filters: SearchFilters,
+    #[serde(default)]
+    pub sort: SortOrder,
+}
+
+impl SavedSearch {
+    fn path(managed_game: &str) -> PathBuf {
+        CONFIG_DIR.join(format!("search_filters_{}.json", managed_game))
+    }
+
+    pub fn load(managed_game: &str) -> anyhow::Result<Self> {
+        let path = Self::path(managed_game);
+        if !std::fs::exists(&path)? {
+            return Ok(Self::default());
+        }
+
+        serde_json::from_reader(BufReader::new(File::open(&path)?))
+            .map_err(|err| anyhow!("Unable to read {}: {}", path.display(), err))
+    }
+
+    pub fn save(&self, managed_game: &str) -> anyhow::Result<()> {
+        std::fs::create_dir_all(CONFIG_DIR.as_path())?;
+        let writer = BufWriter::new(File::create(Self::path(managed_game))?);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+}
+
+impl SearchFilters {
+    /// Whether a package passes every filter. `installed` is only needed when hiding installed
+    /// packages.
+    pub fn matches(
+        &self,
+        package: &SearchablePackage,
+        installed: &HashSet<NamespacedPackage>,
+    ) -> bool {
+        let in_category = |category: &String| {
+            package
+                .categories
+                .iter()
+                .any(|candidate| candidate.eq_ignore_ascii_case(category))
+        };
+
+        (self.include_categories.is_empty() || self.include_categories.iter().any(in_category))
+            && !self.exclude_categories.iter().any(in_category)
+            && (!self.server_side || package.is_server_mod())
+            && !(self.hide_nsfw && package.has_nsfw_content)
+            && !(self.hide_deprecated && package.is_deprecated)
+            && !(self.hide_installed && installed.contains(&NamespacedPackage::from(package)))
+            && self
+                .min_rating
+                .is_none_or(|min_rating| package.rating_score >= min_rating)
+            && self
+                .updated_after
+                .is_none_or(|date| package.date_updated.date_naive() >= date)
+    }
+
+    /// Every category used by the packages, for picking categories to filter by
+    pub fn categories<'a>(
+        packages: impl IntoIterator<Item = &'a SearchablePackage>,
+    ) -> Vec<String> {
+        packages
+            .into_iter()
+            .flat_map(|package| package.categories.iter().cloned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+impl std::fmt::Display for SearchFilters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut active = Vec::new();
+        if !self.include_categories.is_empty() {
+            active.push(self.include_categories.join(" or "));
+        }
+        if !self.exclude_categories.is_empty() {
+            active.push(format!("not {}", self.exclude_categories.join(" or ")));
+        }
+        if self.server_side {
+            active.push("server-side only".to_owned());
+        }
+        if self.hide_nsfw {
+            active.push("no NSFW".to_owned());
+        }
+        if self.hide_deprecated {
+            active.push("no deprecated".to_owned());
+        }
+        if self.hide_installed {
+            active.push("not installed".to_owned());
+        }
+        if let Some(min_rating) = self.min_rating {
+            active.push(format!("rating >= {}", min_rating));
+        }
+        if let Some(date) = self.updated_after {
+            active.push(format!("updated since {}", date));
+        }
+
+        if active.is_empty() {
+            write!(f, "none")
+        } else {
+            write!(f, "{}", active.join(", "))
+        }
+    }
+}
+
+/// A line of the filter editor, showing the current value of a filter
+#[derive(Clone, Copy, PartialEq)]
+enum FilterOption {
+    IncludeCategories,
+    ExcludeCategories,
+    ServerSide,
+    HideNsfw,
+    HideDeprecated,
+    HideInstalled,
+    MinRating,
+    UpdatedAfter,
+    Clear,
+    Done,
+}
+
+impl FilterOption {
+    const VARIANTS: [Self; 10] = [
+        Self::IncludeCategories,
+        Self::ExcludeCategories,
+        Self::ServerSide,
+        Self::HideNsfw,
+        Self::HideDeprecated,
+        Self::HideInstalled,
+        Self::MinRating,
+        Self::UpdatedAfter,
+        Self::Clear,
+        Self::Done,
+    ];
+
+    fn label(&self, filters: &SearchFilters) -> String {
+        let yes_no = |value: bool| if value { "yes" } else { "no" };
+        let list = |values: &Vec<String>, empty: &str| {
+            if values.is_empty() {
+                empty.to_owned()
+            } else {
+                values.join(", ")
+            }
+        };
+
+        match self {
+            Self::IncludeCategories => {
+                format!(
+                    "In categories: {}",
+                    list(&filters.include_categories, "any")
+                )
+            }
+            Self::ExcludeCategories => format!(
+                "Not in categories: {}",
+                list(&filters.exclude_categories, "none")
+            ),
+            Self::ServerSide => format!("Server-side only: {}", yes_no(filters.server_side)),
+            Self::HideNsfw => format!("Hide NSFW: {}", yes_no(filters.hide_nsfw)),
+            Self::HideDeprecated => {
+                format!("Hide deprecated: {}", yes_no(filters.hide_deprecated))
+            }
+            Self::HideInstalled => format!("Hide installed: {}", yes_no(filters.hide_installed)),
+            Self::MinRating => format!(
+                "Minimum rating: {}",
+                filters
+                    .min_rating
+                    .map(|rating| rating.to_string())
+                    .unwrap_or("any".to_owned())
+            ),
+            Self::UpdatedAfter => format!(
+                "Updated since: {}",
+                filters
+                    .updated_after
+                    .map(|date| date.to_string())
+                    .unwrap_or("any time".to_owned())
+            ),
+            Self::Clear => "Clear Filters".to_owned(),
+            Self::Done => "Done".to_owned(),
+        }
+    }
+}
+
+/// A filter editor option along with its label, as the labels change with the filters
+struct LabelledOption(FilterOption, String);
+
+impl std::fmt::Display for LabelledOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.1)
+    }
+}
+
+/// Picks a set of categories, starting with the ones already picked
+fn pick_categories(
+    message: &str,
+    categories: &[String],
+    picked: &[String],
+) -> anyhow::Result<Option<Vec<String>>> {
+    let defaults = categories
+        .iter()
+        .enumerate()
+        .filter(|(_, category)| picked.contains(category))
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+
+    Ok(inquire::MultiSelect::new(message, categories.to_vec())
+        .with_default(&defaults)
+        .prompt_skippable()?)
+}
+
+/// Lets the user change the search filters until they are done, returning the new filters
+pub fn edit(mut filters: SearchFilters, categories: &[String]) -> anyhow::Result<SearchFilters> {
+    let mut cursor = 0;
+    loop {
+        clearscreen::clear()?;
+        let options = FilterOption::VARIANTS
+            .iter()
+            .map(|option| LabelledOption(*option, option.label(&filters)))
+            .collect::<Vec<_>>();
+
+        let options_len = options.len();
+        let Some(LabelledOption(selected, _)) = inquire::Select::new("Search filters", options)
+            .with_starting_cursor(cursor)
+            .with_page_size(options_len)
+            .prompt_skippable()?
+        else {
+            return Ok(filters);
+        };
+        cursor = FilterOption::VARIANTS
+            .iter()
+            .position(|option| *option == selected)
+            .unwrap_or_default();
+
+        match selected {
+            FilterOption::IncludeCategories => {
+                if let Some(picked) = pick_categories(
+                    "Only show packages in any of these categories",
+                    categories,
+                    &filters.include_categories,
+                )? {
+                    filters.include_categories = picked;
+                }
+            }
+            FilterOption::ExcludeCategories => {
+                if let Some(picked) = pick_categories(
+                    "Hide packages in any of these categories",
+                    categories,
+                    &filters.exclude_categories,
+                )? {
+                    filters.exclude_categories = picked;
+                }
+            }
+            FilterOption::ServerSide => filters.server_side = !filters.server_side,
+            FilterOption::HideNsfw => filters.hide_nsfw = !filters.hide_nsfw,
+            FilterOption::HideDeprecated => filters.hide_deprecated = !filters.hide_deprecated,
+            FilterOption::HideInstalled => filters.hide_installed = !filters.hide_installed,
+            FilterOption::MinRating => {
+                if let Some(rating) = inquire::Text::new("Minimum rating")
+                    .with_help_message("Leave empty to show any rating")
+                    .with_validator(|input: &str| {
+                        Ok(
+                            if input.trim().is_empty() || input.trim().parse::<u32>().is_ok() {
+                                inquire::validator::Validation::Valid
+                            } else {
+                                inquire::validator::Validation::Invalid(
+                                    "Expected a whole number".into(),
+                                )
+                            },
+                        )
+                    })
+                    .prompt_skippable()?
+                {
+                    filters.min_rating = rating.trim().parse().ok();
+                }
+            }
+            FilterOption::UpdatedAfter => {
+                if let Some(date) = inquire::Text::new("Only show packages updated since")
+                    .with_help_message("YYYY-MM-DD, or leave empty for any time")
+                    .with_validator(|input: &str| {
+                        Ok(
+                            if input.trim().is_empty() || input.trim().parse::<NaiveDate>().is_ok()
+                            {
+                                inquire::validator::Validation::Valid
+                            } else {
+                                inquire::validator::Validation::Invalid(
+                                    "Expected a date such as 2024-06-30".into(),
+                                )
+                            },
+                        )
+                    })
+                    .prompt_skippable()?
+                {
+                    filters.updated_after = date.trim().parse().ok();
+                }
+            }
+            FilterOption::Clear => filters = SearchFilters::default(),
+            FilterOption::Done => return Ok(filters),
+        }
+    }
+}
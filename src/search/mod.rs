@@ -0,0 +1,469 @@
+mod filters;
+mod sort;
+
+pub use filters::{SavedSearch, SearchFilters, edit as edit_filters};
+pub use sort::{SortOrder, latest_downloads};
+
+use crate::{ProgramState, prelude::*, utils};
+use pad::{Alignment, PadStr};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// The part of a package a word was found in. Matches in some fields count for more than others.
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    Name,
+    Namespace,
+    Category,
+    Description,
+}
+
+impl Field {
+    fn boost(self) -> f64 {
+        match self {
+            Field::Name => 4.0,
+            Field::Namespace => 2.0,
+            Field::Category => 1.5,
+            Field::Description => 1.0,
+        }
+    }
+}
+
+/// A ranked full text index over packages. Packages are referred to by their position in the list
+/// the index was built from.
+pub struct SearchIndex {
+    /// Every distinct word in the indexed packages, along with the packages and fields it appears in
+    words: HashMap<String, Vec<(usize, Field)>>,
+    /// A multiplier for each package which favours popular, well rated packages
+    popularity: Vec<f64>,
+}
+
+/// Splits text into lowercase words, breaking on punctuation as well as on the case changes and
+/// digits within names such as `BetterArcheryMod2`
+fn tokenize(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+
+    for part in text.split(|char: char| !char.is_alphanumeric()) {
+        let mut word = String::new();
+        let mut previous: Option<char> = None;
+
+        for char in part.chars() {
+            let boundary = previous.is_some_and(|previous| {
+                (previous.is_lowercase() && char.is_uppercase())
+                    || (previous.is_alphabetic() != char.is_alphabetic())
+            });
+            if boundary && !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            word.extend(char.to_lowercase());
+            previous = Some(char);
+        }
+        if !word.is_empty() {
+            words.push(word);
+        }
+    }
+
+    words
+}
+
+/// The number of single character insertions, deletions, substitutions and swaps of neighbouring
+/// characters needed to turn `a` into `b`, or `None` if it is more than `max`
+fn edit_distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+
+    let mut before_previous = vec![0; b.len() + 1];
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        current[0] = i;
+        let mut row_min = current[0];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before_previous[j - 2] + 1);
+            }
+            row_min = row_min.min(current[j]);
+        }
+        if row_min > max {
+            return None;
+        }
+        std::mem::swap(&mut before_previous, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    Some(previous[b.len()]).filter(|distance| *distance <= max)
+}
+
+/// How well a single query term matches a word of a package, from 0 to 1
+fn term_score(term: &str, term_chars: &[char], word: &str) -> Option<f64> {
+    if word == term {
+        return Some(1.0);
+    }
+    if word.starts_with(term) {
+        return Some(0.6);
+    }
+    if term.len() >= 3 && word.contains(term) {
+        return Some(0.4);
+    }
+
+    // Longer terms are allowed more typos
+    let max_typos = match term_chars.len() {
+        0..=3 => return None,
+        4..=7 => 1,
+        _ => 2,
+    };
+    let word_chars = word.chars().collect::<Vec<_>>();
+    if let Some(distance) = edit_distance(term_chars, &word_chars, max_typos) {
+        return Some(0.5 - 0.2 * (distance - 1) as f64);
+    }
+
+    // Allow typos in a term which is still being typed as well
+    let prefix = &word_chars[..word_chars.len().min(term_chars.len())];
+    edit_distance(term_chars, prefix, max_typos).map(|distance| 0.3 - 0.1 * (distance - 1) as f64)
+}
+
+impl SearchIndex {
+    pub fn build<'a>(packages: impl IntoIterator<Item = &'a SearchablePackage>) -> Self {
+        let mut words: HashMap<String, Vec<(usize, Field)>> = HashMap::new();
+        let mut popularity = Vec::new();
+
+        for (index, package) in packages.into_iter().enumerate() {
+            let latest = package
+                .versions
+                .iter()
+                .max_by_key(|version| version.number.clone());
+
+            let mut fields = vec![
+                (Field::Name, package.name.clone()),
+                (Field::Namespace, package.namespace.clone()),
+            ];
+            fields.extend(
+                package
+                    .categories
+                    .iter()
+                    .map(|category| (Field::Category, category.clone())),
+            );
+            if let Some(latest) = latest {
+                fields.push((Field::Description, latest.description.clone()));
+            }
+
+            for (field, text) in fields {
+                let mut field_words = tokenize(&text);
+                // Names are also searchable as a single word, so `bepinexpack` finds
+                // `BepInExPack`
+                if field == Field::Name || field == Field::Namespace {
+                    field_words.push(text.to_lowercase().replace(['_', '-', ' '], ""));
+                }
+
+                for word in field_words {
+                    // Packages are added one field at a time, so a repeated word can only be a
+                    // duplicate of the last posting
+                    let postings = words.entry(word).or_default();
+                    if postings.last() != Some(&(index, field)) {
+                        postings.push((index, field));
+                    }
+                }
+            }
+
+            popularity.push(
+                1.0 + (1.0 + package.total_downloads() as f64).ln() / 20.0
+                    + (1.0 + package.rating_score as f64).ln() / 40.0,
+            );
+        }
+
+        Self { words, popularity }
+    }
+
+    /// Finds the packages matching every term of the query, best matches first. Each result is
+    /// the package's position in the list the index was built from, along with its score.
+    pub fn search(&self, query: &str) -> Vec<(usize, f64)> {
+        let terms = tokenize(query);
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        let mut totals = vec![0.0; self.popularity.len()];
+        let mut matched_terms = vec![0; self.popularity.len()];
+
+        for term in &terms {
+            let term_chars = term.chars().collect::<Vec<_>>();
+            let mut best = vec![0.0_f64; self.popularity.len()];
+
+            for (word, postings) in &self.words {
+                let Some(score) = term_score(term, &term_chars, word) else {
+                    continue;
+                };
+                for (index, field) in postings {
+                    best[*index] = best[*index].max(score * field.boost());
+                }
+            }
+
+            for (index, score) in best.into_iter().enumerate() {
+                if score > 0.0 {
+                    totals[index] += score;
+                    matched_terms[index] += 1;
+                }
+            }
+        }
+
+        let mut results = totals
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| matched_terms[*index] == terms.len())
+            .map(|(index, total)| (index, total * self.popularity[index]))
+            .collect::<Vec<_>>();
+        results.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        results
+    }
+}
+
+/// A search result as printed by the `search` command
+#[derive(Serialize)]
+struct SearchResult<'a> {
+    namespace: &'a str,
+    name: &'a str,
+    version: Option<&'a semver::Version>,
+    downloads: u32,
+    rating: u32,
+    categories: Vec<&'a str>,
+    url: &'a str,
+}
+
+impl<'a> From<&'a SearchablePackage> for SearchResult<'a> {
+    fn from(package: &'a SearchablePackage) -> Self {
+        let mut categories = package
+            .categories
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        categories.sort();
+
+        Self {
+            namespace: &package.namespace,
+            name: &package.name,
+            version: package.versions.iter().map(|version| &version.number).max(),
+            downloads: package.total_downloads(),
+            rating: package.rating_score,
+            categories,
+            url: package.package_url.as_str(),
+        }
+    }
+}
+
+/// Prints the packages matching a query and the filters, best matches first unless a sort order
+/// is given. Without a query every package passing the filters is listed.
+pub async fn perform_search(
+    mut program_state: ProgramState,
+    api: &thunderstore::Client,
+    query: Option<String>,
+    filters: &SearchFilters,
+    sort: Option<SortOrder>,
+    limit: Option<usize>,
+    json: bool,
+) -> anyhow::Result<()> {
+    if program_state.packages.is_empty() {
+        // Progress would break the output when it is read as JSON
+        if !json {
+            println!("Refreshing packages...");
+        }
+        program_state.refresh_packages(api).await?;
+    }
+
+    let installed = if filters.hide_installed {
+        utils::get_installed_mods(&program_state)?
+            .into_iter()
+            .map(|installed| installed.namespaced)
+            .collect()
+    } else {
+        HashSet::new()
+    };
+
+    let mut packages = program_state
+        .packages
+        .values()
+        .filter(|package| filters.matches(package, &installed))
+        .collect::<Vec<_>>();
+    let sort = sort.unwrap_or_default();
+    packages.sort_by(|a, b| sort.compare(a, b));
+
+    let mut results = match query.filter(|query| !query.trim().is_empty()) {
+        Some(query) => {
+            let index = SearchIndex::build(packages.iter().copied());
+            let mut ranked = index
+                .search(&query)
+                .into_iter()
+                .map(|(position, _)| packages[position])
+                .collect::<Vec<_>>();
+            if sort != SortOrder::Relevance {
+                ranked.sort_by(|a, b| sort.compare(a, b));
+            }
+            ranked
+        }
+        None => packages,
+    };
+    results.truncate(limit.unwrap_or(results.len()));
+
+    let results = results
+        .into_iter()
+        .map(SearchResult::from)
+        .collect::<Vec<_>>();
+
+    if json {
+        for result in &results {
+            println!("{}", serde_json::to_string(result)?);
+        }
+        return Ok(());
+    }
+
+    if results.is_empty() {
+        println!("No packages found");
+        return Ok(());
+    }
+
+    let rows = results
+        .iter()
+        .map(|result| {
+            [
+                format!("{}/{}", result.namespace, result.name),
+                result
+                    .version
+                    .map(|version| version.to_string())
+                    .unwrap_or_default(),
+                result.downloads.to_string(),
+                result.rating.to_string(),
+                result.categories.join(", "),
+                result.url.to_owned(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let header = [
+        "Package",
+        "Version",
+        "Downloads",
+        "Rating",
+        "Categories",
+        "URL",
+    ]
+    .map(String::from);
+
+    let mut widths = header.clone().map(|title| title.len());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    for row in std::iter::once(&header).chain(&rows) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| cell.pad_to_width_with_alignment(width, Alignment::Left))
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, description: &str, downloads: u32) -> SearchablePackage {
+        let package = serde_json::json!({
+            "uuid4": "00000000-0000-0000-0000-000000000000",
+            "owner": "Author",
+            "name": name,
+            "full_name": format!("Author-{}", name),
+            "categories": [],
+            "date_created": "2024-01-01T00:00:00Z",
+            "date_updated": "2024-01-01T00:00:00Z",
+            "donation_link": null,
+            "has_nsfw_content": false,
+            "is_deprecated": false,
+            "is_pinned": false,
+            "package_url": "https://thunderstore.io/",
+            "rating_score": 0,
+            "versions": [{
+                "uuid4": "00000000-0000-0000-0000-000000000000",
+                "name": name,
+                "version_number": "1.0.0",
+                "full_name": format!("Author-{}-1.0.0", name),
+                "date_created": "2024-01-01T00:00:00Z",
+                "dependencies": [],
+                "description": description,
+                "download_url": "https://thunderstore.io/",
+                "downloads": downloads,
+                "file_size": 0,
+                "icon": "https://thunderstore.io/",
+                "is_active": true,
+                "website_url": "",
+            }],
+        });
+        SearchablePackage(serde_json::from_value(package).unwrap())
+    }
+
+    #[test]
+    fn names_are_split_into_words() {
+        assert_eq!(
+            tokenize("BetterArcheryMod2"),
+            ["better", "archery", "mod", "2"]
+        );
+        assert_eq!(tokenize("server_devcommands"), ["server", "devcommands"]);
+        assert_eq!(tokenize("  Plant-Everything!  "), ["plant", "everything"]);
+        assert!(tokenize("--").is_empty());
+    }
+
+    #[test]
+    fn edit_distance_counts_typos() {
+        let chars = |text: &str| text.chars().collect::<Vec<_>>();
+
+        assert_eq!(
+            edit_distance(&chars("archery"), &chars("archery"), 2),
+            Some(0)
+        );
+        assert_eq!(
+            edit_distance(&chars("archry"), &chars("archery"), 2),
+            Some(1)
+        );
+        assert_eq!(
+            edit_distance(&chars("arhcery"), &chars("archery"), 2),
+            Some(1)
+        );
+        assert_eq!(edit_distance(&chars("archer"), &chars("ranger"), 2), None);
+        assert_eq!(edit_distance(&chars("a"), &chars("archery"), 2), None);
+    }
+
+    #[test]
+    fn better_matches_are_ranked_first() {
+        let packages = [
+            package("PlantEverything", "Lets you plant anything", 10),
+            package("Archery", "Better bows", 10),
+            package("BetterArchery", "Archery overhaul", 5),
+            package("Farming", "More ways to farm archery targets", 1_000_000),
+        ];
+        let index = SearchIndex::build(&packages);
+
+        let ranked = |query: &str| {
+            index
+                .search(query)
+                .into_iter()
+                .map(|(position, _)| packages[position].name.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(ranked("archery"), ["Archery", "BetterArchery", "Farming"]);
+        assert_eq!(ranked("better archery"), ["BetterArchery", "Archery"]);
+        // Typos and words which are still being typed find the package as well
+        assert_eq!(ranked("plant everyting"), ["PlantEverything"]);
+        assert_eq!(ranked("plant every"), ["PlantEverything"]);
+        assert!(ranked("nothing like it").is_empty());
+    }
+}
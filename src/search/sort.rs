@@ -0,0 +1,45 @@
+use crate::prelude::*;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+enum_select! {
+    /// The orders packages can be listed in
+    #[derive(ValueEnum, Deserialize, Serialize, Clone, Copy, PartialEq, Debug, Default)]
+    pub enum SortOrder {
+        #[default]
+        Relevance = "Relevance",
+        Downloads = "Downloads",
+        LatestDownloads = "Latest version downloads",
+        Rating = "Rating",
+        Updated = "Date updated",
+        Created = "Date created",
+        Name = "Name",
+    }
+}
+
+impl SortOrder {
+    /// Compares two packages, placing the package which should be listed first before the other.
+    /// Names are listed alphabetically, and everything else highest first. Packages are listed by
+    /// relevance only once there is a query to rank them against, and by downloads until then.
+    pub fn compare(&self, a: &SearchablePackage, b: &SearchablePackage) -> Ordering {
+        match self {
+            Self::Relevance | Self::Downloads => b.total_downloads().cmp(&a.total_downloads()),
+            Self::LatestDownloads => latest_downloads(b).cmp(&latest_downloads(a)),
+            Self::Rating => b.rating_score.cmp(&a.rating_score),
+            Self::Updated => b.date_updated.cmp(&a.date_updated),
+            Self::Created => b.date_created.cmp(&a.date_created),
+            Self::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        }
+    }
+}
+
+/// The downloads of the newest version of a package
+pub fn latest_downloads(package: &SearchablePackage) -> u32 {
+    package
+        .versions
+        .iter()
+        .max_by_key(|version| version.number.clone())
+        .map(|version| version.downloads)
+        .unwrap_or_default()
+}
@@ -0,0 +1,158 @@
+mod wizard;
+
+pub use wizard::run_wizard;
+
+use crate::prelude::*;
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::PathBuf,
+};
+
+/// Defaults which only apply to a single game
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct GameDefaults {
+    /// The mods directory used for the game when neither `--mods-dir` nor the profile provide one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mods_dir: Option<PathBuf>,
+}
+
+/// Defaults shared by every invocation, so that `--managed-game` and `--mods-dir` don't have to be
+/// passed every time
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct GlobalSettings {
+    /// The game managed when `--managed-game` is not provided and no project manifest is found
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_game: Option<String>,
+    /// The mods directory used when no other mods directory is configured for a game
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mods_dir: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub games: BTreeMap<String, GameDefaults>,
+}
+
+/// A setting which can be read and changed with the `config` command
+enum SettingKey {
+    DefaultGame,
+    ModsDir,
+    GameModsDir(String),
+}
+
+impl SettingKey {
+    fn parse(key: &str) -> anyhow::Result<Self> {
+        match key.split('.').collect::<Vec<_>>().as_slice() {
+            ["default_game"] => Ok(Self::DefaultGame),
+            ["mods_dir"] => Ok(Self::ModsDir),
+            ["games", game, "mods_dir"] if !game.is_empty() => {
+                Ok(Self::GameModsDir((*game).to_owned()))
+            }
+            _ => Err(anyhow!(
+                "Unknown setting {}. Expected one of default_game, mods_dir or games.<game>.mods_dir",
+                key
+            )),
+        }
+    }
+}
+
+impl GlobalSettings {
+    pub fn path() -> PathBuf {
+        CONFIG_DIR.join("settings.json")
+    }
+
+    /// Loads the global settings. Nothing is configured until the settings file is first written.
+    pub fn load() -> anyhow::Result<Self> {
+        let path = Self::path();
+        if !std::fs::exists(&path)? {
+            return Ok(Self::default());
+        }
+
+        serde_json::from_reader(BufReader::new(File::open(&path)?))
+            .map_err(|err| anyhow!("Unable to read {}: {}", path.display(), err))
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        std::fs::create_dir_all(CONFIG_DIR.as_path())?;
+        let writer = BufWriter::new(File::create(Self::path())?);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    /// The default mods directory for a game, preferring the game's own default over the global one
+    pub fn mods_dir_for(&self, managed_game: &str) -> Option<PathBuf> {
+        self.games
+            .get(managed_game)
+            .and_then(|defaults| defaults.mods_dir.clone())
+            .or(self.mods_dir.clone())
+    }
+
+    fn get(&self, key: &SettingKey) -> Option<String> {
+        match key {
+            SettingKey::DefaultGame => self.default_game.clone(),
+            SettingKey::ModsDir => self.mods_dir.as_ref().map(|dir| dir.display().to_string()),
+            SettingKey::GameModsDir(game) => self
+                .games
+                .get(game)
+                .and_then(|defaults| defaults.mods_dir.as_ref())
+                .map(|dir| dir.display().to_string()),
+        }
+    }
+
+    fn set(&mut self, key: &SettingKey, value: Option<String>) -> anyhow::Result<()> {
+        let as_dir = |value: Option<String>| value.map(std::path::absolute).transpose();
+        match key {
+            SettingKey::DefaultGame => self.default_game = value,
+            SettingKey::ModsDir => self.mods_dir = as_dir(value)?,
+            SettingKey::GameModsDir(game) => {
+                self.games.entry(game.clone()).or_default().mods_dir = as_dir(value)?;
+                if self.games[game].mods_dir.is_none() {
+                    self.games.remove(game);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Prints a single setting, or every configured setting when no key is given
+pub fn perform_get(key: Option<String>) -> anyhow::Result<()> {
+    let settings = GlobalSettings::load()?;
+
+    if let Some(key) = key {
+        match settings.get(&SettingKey::parse(&key)?) {
+            Some(value) => println!("{}", value),
+            None => return Err(anyhow!("{} is not set", key)),
+        }
+        return Ok(());
+    }
+
+    let mut keys = vec!["default_game".to_owned(), "mods_dir".to_owned()];
+    keys.extend(
+        settings
+            .games
+            .keys()
+            .map(|game| format!("games.{}.mods_dir", game)),
+    );
+    for key in keys {
+        if let Some(value) = settings.get(&SettingKey::parse(&key)?) {
+            println!("{} = {}", key, value);
+        }
+    }
+    Ok(())
+}
+
+/// Changes a single setting. A `None` value removes the setting.
+pub fn perform_set(key: &str, value: Option<String>) -> anyhow::Result<()> {
+    let key_parsed = SettingKey::parse(key)?;
+    let mut settings = GlobalSettings::load()?;
+    settings.set(&key_parsed, value)?;
+    settings.save()?;
+
+    match settings.get(&key_parsed) {
+        Some(value) => println!("{} = {}", key, value),
+        None => println!("Removed {}", key),
+    }
+    Ok(())
+}
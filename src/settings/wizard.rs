@@ -0,0 +1,92 @@
+use super::{GameDefaults, GlobalSettings};
+use colored::Colorize;
+use inquire::validator::Validation;
+use std::{fmt::Display, path::PathBuf};
+use thunderstore::models::Community;
+
+/// A Thunderstore community shown by its display name along with the identifier used by the api
+struct CommunityOption(Community);
+
+impl Display for CommunityOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.0.name, self.0.ident)
+    }
+}
+
+/// Fetches every page of Thunderstore communities, sorted by name
+async fn fetch_communities(api: &thunderstore::Client) -> anyhow::Result<Vec<CommunityOption>> {
+    let mut communities = Vec::new();
+    let mut cursor: Option<String> = None;
+
+    loop {
+        let (state, page) = api.get_communities(cursor.as_deref()).await?;
+        communities.extend(page.into_iter().map(CommunityOption));
+
+        match state.next {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+
+    communities.sort_by_key(|community| community.0.name.to_lowercase());
+    Ok(communities)
+}
+
+/// Asks for the game to manage and where its mods are deployed, then saves them as the defaults.
+/// Used the first time the program is run without a game being configured anywhere.
+pub async fn run_wizard(api: &thunderstore::Client) -> anyhow::Result<()> {
+    clearscreen::clear()?;
+    println!("No game has been configured yet. Let's set up some defaults.\n");
+    println!("Fetching Thunderstore communities...");
+
+    let managed_game = match fetch_communities(api).await {
+        Ok(communities) if !communities.is_empty() => {
+            inquire::Select::new("Which game should be managed?", communities)
+                .prompt()?
+                .0
+                .ident
+        }
+        Ok(_) | Err(_) => {
+            println!(
+                "{}",
+                "Unable to fetch the list of communities from Thunderstore".yellow()
+            );
+            inquire::Text::new("Which game should be managed?")
+                .with_help_message("The community identifier used by Thunderstore. Ex. valheim")
+                .prompt()?
+        }
+    };
+
+    let mods_dir = inquire::Text::new("Where should mods be deployed?")
+        .with_help_message("Usually the BepInEx/plugins folder of the game or server")
+        .with_validator(|input: &str| {
+            let path = PathBuf::from(input);
+            Ok(if input.is_empty() {
+                Validation::Invalid("A mods directory is required".into())
+            } else if !path.exists() {
+                Validation::Invalid("This directory does not exist".into())
+            } else if !path.is_dir() {
+                Validation::Invalid("This is not a directory".into())
+            } else {
+                Validation::Valid
+            })
+        })
+        .prompt()?;
+    let mods_dir = std::path::absolute(mods_dir)?;
+
+    let mut settings = GlobalSettings::load()?;
+    settings.default_game = Some(managed_game.clone());
+    settings.games.insert(
+        managed_game,
+        GameDefaults {
+            mods_dir: Some(mods_dir),
+        },
+    );
+    settings.save()?;
+
+    println!(
+        "Saved to {}. These can be changed later with the config command\n",
+        GlobalSettings::path().display()
+    );
+    Ok(())
+}
@@ -0,0 +1,429 @@
+use crate::{ProgramState, prelude::*, utils};
+use anyhow::anyhow;
+use chrono::{DateTime, Local};
+use colored::Colorize;
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use pad::{Alignment, PadStr};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+/// The contents of the mods directory, disabled mods, game config and this program's own state
+/// files at a point in time. The game config is only included when the mods are deployed to
+/// `BepInEx/plugins`. File contents are stored once in a shared, compressed object store and
+/// referenced here by their hash.
+#[derive(Deserialize, Serialize)]
+struct Snapshot {
+    created: DateTime<Local>,
+    label: Option<String>,
+    /// Snapshotted areas, each mapping relative file paths to the hash of their contents
+    areas: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+fn objects_dir() -> PathBuf {
+    CACHE_DIR.join("snapshots").join("objects")
+}
+
+/// Every snapshot file of every game, profile and project. They all share the object store.
+fn all_snapshot_files() -> anyhow::Result<Vec<PathBuf>> {
+    let root = CACHE_DIR.join("snapshots");
+    Ok(utils::walk_files(&root)?
+        .into_values()
+        .filter(|path| {
+            !path.starts_with(objects_dir())
+                && path
+                    .extension()
+                    .is_some_and(|extension| extension == "json")
+        })
+        .collect())
+}
+
+fn snapshots_dir(program_state: &ProgramState) -> PathBuf {
+    match &program_state.project {
+        Some(manifest_path) => crate::profile::snapshots_dir(
+            &program_state.managed_game,
+            &crate::project::snapshots_name(manifest_path),
+        ),
+        None => crate::profile::snapshots_dir(&program_state.managed_game, &program_state.profile),
+    }
+}
+
+/// Every directory captured in a snapshot, keyed by the area name stored in the snapshot
+fn area_roots(program_state: &ProgramState) -> Vec<(&'static str, PathBuf)> {
+    let mut roots = vec![
+        ("mods", program_state.mods_dir.clone()),
+        ("disabled", program_state.disabled_dir()),
+    ];
+    if let Some(config_dir) = program_state.game_config_dir() {
+        roots.push(("config", config_dir));
+    }
+    roots
+}
+
+/// One of this program's own files captured in the `state` area of a snapshot
+struct StateFile {
+    /// Where the file currently is
+    path: PathBuf,
+    /// Where the file would be if it was written in another format
+    alternatives: Vec<PathBuf>,
+    /// The key the file was stored under by snapshots which didn't record its real name
+    legacy_key: &'static str,
+}
+
+impl StateFile {
+    /// The key the file is stored under, which is its file name
+    fn key(&self) -> String {
+        self.path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// The stored file a snapshot holds for this one, along with where it is restored to. The
+    /// requirements may have been written in another format when the snapshot was taken, in which
+    /// case that file is restored in place of the current one.
+    fn find_in<'a>(&self, stored: &'a BTreeMap<String, String>) -> Option<(PathBuf, &'a String)> {
+        std::iter::once(&self.path)
+            .chain(&self.alternatives)
+            .find_map(|path| {
+                let key = path.file_name()?.to_str()?;
+                stored.get(key).map(|hash| (path.clone(), hash))
+            })
+            .or_else(|| {
+                stored
+                    .get(self.legacy_key)
+                    .map(|hash| (self.path.clone(), hash))
+            })
+    }
+}
+
+/// The individual state files captured in the `state` area of a snapshot
+fn state_files(program_state: &ProgramState) -> Vec<StateFile> {
+    let requirements_path = program_state.requirements_path();
+    vec![
+        StateFile {
+            path: requirements_path.clone(),
+            // A project's requirements are always kept in its manifest
+            alternatives: match program_state.project {
+                Some(_) => Vec::new(),
+                None => crate::requirements::other_formats(&requirements_path),
+            },
+            legacy_key: "requirements.json",
+        },
+        StateFile {
+            path: program_state.lockfile_path(),
+            alternatives: Vec::new(),
+            legacy_key: "lock.json",
+        },
+    ]
+}
+
+/// Removes the directories left empty by removing `file`, up to but not including `root`
+fn remove_empty_parents(root: &Path, file: &Path) {
+    for dir in file.ancestors().skip(1) {
+        if dir == root || !dir.starts_with(root) || std::fs::remove_dir(dir).is_err() {
+            break;
+        }
+    }
+}
+
+/// Hashes the current files on disk, optionally writing any new contents to the object store
+fn capture(
+    program_state: &ProgramState,
+    store: bool,
+) -> anyhow::Result<BTreeMap<String, BTreeMap<String, String>>> {
+    let mut areas = BTreeMap::new();
+
+    let capture_file = |path: &PathBuf| -> anyhow::Result<String> {
+        let contents = std::fs::read(path)?;
+        let hash = utils::hash_bytes(&contents);
+        if store {
+            write_object(&hash, &contents)?;
+        }
+        Ok(hash)
+    };
+
+    for (area, root) in area_roots(program_state) {
+        let mut files = BTreeMap::new();
+        for (key, path) in utils::walk_files(&root)? {
+            files.insert(key, capture_file(&path)?);
+        }
+        areas.insert(area.to_owned(), files);
+    }
+
+    let mut state = BTreeMap::new();
+    for file in state_files(program_state) {
+        if std::fs::exists(&file.path)? {
+            state.insert(file.key(), capture_file(&file.path)?);
+        }
+    }
+    areas.insert("state".to_owned(), state);
+
+    Ok(areas)
+}
+
+fn write_object(hash: &str, contents: &[u8]) -> anyhow::Result<()> {
+    let path = objects_dir().join(hash);
+    if std::fs::exists(&path)? {
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(objects_dir())?;
+    let mut encoder = GzEncoder::new(BufWriter::new(File::create(path)?), Compression::default());
+    encoder.write_all(contents)?;
+    encoder.finish()?.flush()?;
+    Ok(())
+}
+
+fn read_object(hash: &str) -> anyhow::Result<Vec<u8>> {
+    let file = File::open(objects_dir().join(hash))
+        .map_err(|_| anyhow!("Snapshot object {} is missing from the store", hash))?;
+    let mut contents = Vec::new();
+    GzDecoder::new(BufReader::new(file)).read_to_end(&mut contents)?;
+    Ok(contents)
+}
+
+fn load_snapshot(program_state: &ProgramState, id: i64) -> anyhow::Result<Snapshot> {
+    let file = File::open(snapshots_dir(program_state).join(format!("{}.json", id)))
+        .map_err(|_| anyhow!("No snapshot with the id {} exists", id))?;
+    Ok(serde_json::from_reader(BufReader::new(file))?)
+}
+
+fn list_snapshots(program_state: &ProgramState) -> anyhow::Result<Vec<(i64, Snapshot)>> {
+    let dir = snapshots_dir(program_state);
+    let mut snapshots = Vec::new();
+    if !std::fs::exists(&dir)? {
+        return Ok(snapshots);
+    }
+
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(id) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse::<i64>().ok())
+        else {
+            continue;
+        };
+        snapshots.push((id, load_snapshot(program_state, id)?));
+    }
+
+    snapshots.sort_by_key(|(id, _)| *id);
+    Ok(snapshots)
+}
+
+/// Captures the current state into a new snapshot, returning its id
+pub fn create_snapshot(program_state: &ProgramState, label: Option<String>) -> anyhow::Result<i64> {
+    let created = Local::now();
+    let snapshot = Snapshot {
+        created,
+        label,
+        areas: capture(program_state, true)?,
+    };
+
+    let dir = snapshots_dir(program_state);
+    std::fs::create_dir_all(&dir)?;
+
+    // Snapshots taken within the same second share a timestamp, so bump the id until it is free
+    let mut id = created.timestamp();
+    while std::fs::exists(dir.join(format!("{}.json", id)))? {
+        id += 1;
+    }
+
+    let writer = BufWriter::new(File::create(dir.join(format!("{}.json", id)))?);
+    serde_json::to_writer_pretty(writer, &snapshot)?;
+
+    Ok(id)
+}
+
+pub fn perform_create(program_state: &ProgramState, label: Option<String>) -> anyhow::Result<()> {
+    let id = create_snapshot(program_state, label)?;
+    println!("Created snapshot {}", id);
+    Ok(())
+}
+
+pub fn perform_list(program_state: &ProgramState) -> anyhow::Result<()> {
+    let snapshots = list_snapshots(program_state)?;
+    if snapshots.is_empty() {
+        println!(
+            "No snapshots have been taken for the {} profile",
+            program_state.profile
+        );
+        return Ok(());
+    }
+
+    for (id, snapshot) in snapshots {
+        let file_count: usize = snapshot.areas.values().map(BTreeMap::len).sum();
+        println!(
+            "{}{}{}{}",
+            id.to_string()
+                .pad_to_width_with_alignment(14, Alignment::Left)
+                .cyan(),
+            snapshot
+                .created
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+                .pad_to_width_with_alignment(22, Alignment::Left),
+            format!("{} files", file_count).pad_to_width_with_alignment(14, Alignment::Left),
+            snapshot.label.as_deref().unwrap_or("")
+        );
+    }
+
+    Ok(())
+}
+
+/// Prints the files which differ between two snapshots. When `to` is not given, the snapshot is
+/// compared against the files currently on disk.
+pub fn perform_diff(
+    program_state: &ProgramState,
+    from: i64,
+    to: Option<i64>,
+) -> anyhow::Result<()> {
+    let from = load_snapshot(program_state, from)?.areas;
+    let to = match to {
+        Some(to) => load_snapshot(program_state, to)?.areas,
+        None => capture(program_state, false)?,
+    };
+
+    let empty = BTreeMap::new();
+    let mut area_names = from.keys().chain(to.keys()).collect::<Vec<_>>();
+    area_names.sort();
+    area_names.dedup();
+
+    let mut differences = 0;
+    for area in area_names {
+        let before = from.get(area).unwrap_or(&empty);
+        let after = to.get(area).unwrap_or(&empty);
+
+        for (file, hash) in before {
+            match after.get(file) {
+                None => println!("{} {}/{}", "removed: ".red(), area, file),
+                Some(after_hash) if after_hash != hash => {
+                    println!("{} {}/{}", "modified:".yellow(), area, file)
+                }
+                Some(_) => continue,
+            }
+            differences += 1;
+        }
+
+        for file in after.keys().filter(|file| !before.contains_key(*file)) {
+            println!("{} {}/{}", "added:   ".green(), area, file);
+            differences += 1;
+        }
+    }
+
+    if differences == 0 {
+        println!("No differences");
+    }
+
+    Ok(())
+}
+
+/// Restores the mods directory, disabled mods, game config and state files to exactly what they
+/// were when the snapshot was taken. The current state is snapshotted first so that a restore can
+/// itself be undone.
+pub fn perform_restore(program_state: &ProgramState, id: i64) -> anyhow::Result<()> {
+    let snapshot = load_snapshot(program_state, id)?;
+    let backup = create_snapshot(program_state, Some(format!("before restoring {}", id)))?;
+
+    let empty = BTreeMap::new();
+    for (area, root) in area_roots(program_state) {
+        let wanted = snapshot.areas.get(area).unwrap_or(&empty);
+        let on_disk = utils::hash_dir(&root)?;
+
+        for file in on_disk.keys().filter(|file| !wanted.contains_key(*file)) {
+            let path = root.join(file);
+            std::fs::remove_file(&path)?;
+            remove_empty_parents(&root, &path);
+        }
+
+        for (file, hash) in wanted {
+            if on_disk.get(file) == Some(hash) {
+                continue;
+            }
+            let path = root.join(file);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, read_object(hash)?)?;
+        }
+    }
+
+    let wanted_state = snapshot.areas.get("state").unwrap_or(&empty);
+    for file in state_files(program_state) {
+        match file.find_in(wanted_state) {
+            Some((path, hash)) => {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&path, read_object(hash)?)?;
+                if path != file.path && std::fs::exists(&file.path)? {
+                    std::fs::remove_file(&file.path)?;
+                }
+            }
+            None if std::fs::exists(&file.path)? => std::fs::remove_file(&file.path)?,
+            None => {}
+        }
+    }
+
+    println!(
+        "Restored snapshot {}. The previous state was saved as snapshot {}",
+        id, backup
+    );
+    Ok(())
+}
+
+/// Removes every object in the store which no snapshot of any game or profile refers to, returning
+/// how many were removed
+fn remove_unused_objects() -> anyhow::Result<usize> {
+    let mut used = HashSet::new();
+    for path in all_snapshot_files()? {
+        let snapshot: Snapshot = serde_json::from_reader(BufReader::new(File::open(&path)?))
+            .map_err(|err| anyhow!("Unable to read {}: {}", path.display(), err))?;
+        used.extend(snapshot.areas.into_values().flat_map(BTreeMap::into_values));
+    }
+
+    let mut removed = 0;
+    for (hash, path) in utils::walk_files(&objects_dir())? {
+        if !used.contains(&hash) {
+            std::fs::remove_file(path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Deletes a snapshot along with the stored files only it referred to
+pub fn perform_delete(program_state: &ProgramState, id: i64) -> anyhow::Result<()> {
+    let path = snapshots_dir(program_state).join(format!("{}.json", id));
+    if !std::fs::exists(&path)? {
+        return Err(anyhow!("No snapshot with the id {} exists", id));
+    }
+    std::fs::remove_file(path)?;
+
+    let removed = remove_unused_objects()?;
+    println!("Deleted snapshot {} and {} stored files", id, removed);
+    Ok(())
+}
+
+/// Deletes all but the newest `keep` snapshots of the profile when given, then removes the stored
+/// files no remaining snapshot refers to
+pub fn perform_prune(program_state: &ProgramState, keep: Option<usize>) -> anyhow::Result<()> {
+    if let Some(keep) = keep {
+        let snapshots = list_snapshots(program_state)?;
+        let to_delete = snapshots.len().saturating_sub(keep);
+        for (id, _) in &snapshots[..to_delete] {
+            std::fs::remove_file(snapshots_dir(program_state).join(format!("{}.json", id)))?;
+        }
+        println!("Deleted {} snapshots", to_delete);
+    }
+
+    let removed = remove_unused_objects()?;
+    println!("Removed {} stored files no snapshot uses", removed);
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use crate::{ProgramState, prelude::NamespacedPackage};
+use anyhow::anyhow;
+
+/// Moves an installed mod between the mods directory and the disabled mods folder. The mod stays in
+/// the requirements and lockfile so that it continues to be tracked by updates.
+pub fn set_enabled(
+    program_state: &mut ProgramState,
+    mod_name: &NamespacedPackage,
+    enabled: bool,
+) -> anyhow::Result<()> {
+    let installed = crate::utils::get_installed_mods(program_state)?
+        .into_iter()
+        .find(|installed| &installed.namespaced == mod_name)
+        .ok_or(anyhow!("{} is not installed", mod_name))?;
+
+    if installed.enabled != enabled {
+        let (from_root, to_root) = if enabled {
+            (program_state.disabled_dir(), program_state.mods_dir.clone())
+        } else {
+            (program_state.mods_dir.clone(), program_state.disabled_dir())
+        };
+
+        let destination = to_root.join(installed.dir.strip_prefix(&from_root)?);
+        if std::fs::exists(&destination)? {
+            return Err(anyhow!(
+                "Unable to move {}, {} already exists",
+                mod_name,
+                destination.display()
+            ));
+        }
+
+        if let Some(parent) = destination.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::rename(&installed.dir, &destination)?;
+    }
+
+    if let Some(requirement) = program_state.requirements.get_mut(mod_name) {
+        requirement.enabled = enabled;
+        program_state.save_requirements()?;
+    }
+
+    println!(
+        "{} {}",
+        if enabled { "Enabled" } else { "Disabled" },
+        mod_name
+    );
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use crate::{ProgramState, prelude::NamespacedPackage};
+use anyhow::anyhow;
+
+/// Removes an installed mod from the mods directory, as well as from the requirements and lockfile
+pub fn perform_uninstall(
+    program_state: &mut ProgramState,
+    mod_name: &NamespacedPackage,
+) -> anyhow::Result<()> {
+    let install_dir = crate::utils::get_installed_mods(program_state)?
+        .into_iter()
+        .find(|installed| &installed.namespaced == mod_name)
+        .map(|installed| installed.dir);
+
+    let was_required = program_state.requirements.remove(mod_name).is_some();
+    let was_locked = program_state.lockfile.remove(mod_name).is_some();
+
+    if install_dir.is_none() && !was_required && !was_locked {
+        return Err(anyhow!("{} is not installed", mod_name));
+    }
+
+    if let Some(install_dir) = install_dir {
+        std::fs::remove_dir_all(install_dir)?;
+    }
+
+    program_state.save_requirements()?;
+    program_state.save_lockfile()?;
+
+    println!("Uninstalled {}", mod_name);
+    Ok(())
+}
@@ -1,10 +1,168 @@
 use crate::{ProgramState, prelude::NamespacedPackage};
+use anyhow::anyhow;
+use colored::Colorize;
 
 pub async fn perform_update(
-    program_state: ProgramState,
+    mut program_state: ProgramState,
+    api: &thunderstore::Client,
     mod_name: Option<NamespacedPackage>,
     update_cache: bool,
     dry_run: bool,
+    changelog: bool,
 ) -> anyhow::Result<()> {
-    todo!()
+    if update_cache || program_state.packages.is_empty() {
+        println!("Refreshing packages...");
+        program_state.refresh_packages(api).await?;
+    }
+
+    let mut to_update = match mod_name {
+        Some(mod_name) => {
+            if program_state.requirements.get(&mod_name).is_none() {
+                return Err(anyhow!("{} is not in the requirements file", mod_name));
+            }
+            vec![mod_name]
+        }
+        None => program_state
+            .requirements
+            .iter()
+            .map(|(namespaced, _)| namespaced.clone())
+            .collect(),
+    };
+    to_update.sort();
+
+    let mut updated_any = false;
+    let mut changed = Vec::new();
+    for namespaced in to_update {
+        if let Some(requirement) = program_state.requirements.get(&namespaced)
+            && requirement.held
+        {
+            println!(
+                "{}",
+                format!(
+                    "Skipping {}, it is held{}",
+                    namespaced,
+                    requirement
+                        .hold_reason
+                        .as_ref()
+                        .map(|reason| format!(": {}", reason))
+                        .unwrap_or_default()
+                )
+                .yellow()
+            );
+            continue;
+        }
+
+        let Some(latest) = program_state
+            .requirements
+            .get_latest_version(&program_state.packages, &namespaced)
+        else {
+            println!(
+                "{}",
+                format!("No version of {} matches the requirements", namespaced).yellow()
+            );
+            continue;
+        };
+
+        let installed_version = program_state
+            .lockfile
+            .get(&namespaced)
+            .map(|locked| locked.version.clone());
+
+        if installed_version
+            .as_ref()
+            .is_some_and(|installed| *installed >= latest.ident.parsed_version())
+        {
+            continue;
+        }
+
+        let from = installed_version
+            .as_ref()
+            .map(|version| version.to_string())
+            .unwrap_or("not installed".into());
+        println!(
+            "{}: {} -> {}",
+            namespaced,
+            from,
+            latest.ident.version().green()
+        );
+
+        if changelog && let Some(installed_version) = &installed_version {
+            print_changes(api, installed_version, &latest.ident).await?;
+        }
+
+        if !dry_run {
+            if !updated_any && program_state.settings.auto_snapshot {
+                let id =
+                    crate::snapshot::create_snapshot(&program_state, Some("before update".into()))?;
+                println!("Saved the current mods as snapshot {}", id);
+            }
+
+            for dependency in crate::install::resolve_install_set(&program_state, &latest)? {
+                let dependency_name = NamespacedPackage::from(&dependency.ident);
+                if !changed
+                    .iter()
+                    .any(|(namespaced, _)| *namespaced == dependency_name)
+                {
+                    let previous = program_state
+                        .lockfile
+                        .get(&dependency_name)
+                        .map(|locked| locked.version.clone());
+                    changed.push((dependency_name, previous));
+                }
+
+                crate::install::install_version(&mut program_state, api, &dependency.ident).await?;
+            }
+        }
+        updated_any = true;
+    }
+
+    if !updated_any {
+        println!("All mods are up to date");
+    } else if !dry_run {
+        program_state.lockfile.last_operation = changed;
+        program_state.save_lockfile()?;
+    }
+
+    Ok(())
+}
+
+/// Prints the changelog entries between the installed version and the update target, indented
+/// below the update they belong to
+async fn print_changes(
+    api: &thunderstore::Client,
+    installed: &semver::Version,
+    target: &thunderstore::VersionIdent,
+) -> anyhow::Result<()> {
+    let changes = match crate::docs::changes_since(api, installed, target).await? {
+        crate::docs::Changes::Entries(changes) if !changes.trim().is_empty() => changes,
+        crate::docs::Changes::Entries(_) => {
+            println!(
+                "    {}",
+                "The changelog has no entries for these versions".dimmed()
+            );
+            return Ok(());
+        }
+        crate::docs::Changes::NoHeadings => {
+            println!(
+                "    {}",
+                "The changelog doesn't list its changes by version".dimmed()
+            );
+            return Ok(());
+        }
+        crate::docs::Changes::NoChangelog => {
+            println!("    {}", "No changelog was published".dimmed());
+            return Ok(());
+        }
+    };
+
+    let (width, _) = term_size::dimensions().unwrap_or((100, 0));
+    for line in crate::docs::render(&changes, width.saturating_sub(5).max(20)) {
+        if line.is_empty() {
+            println!();
+        } else {
+            println!("    {}", line);
+        }
+    }
+    println!();
+    Ok(())
 }
@@ -1,5 +1,161 @@
 use super::ProgramState;
+use crate::prelude::*;
+use anyhow::anyhow;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    path::{Path, PathBuf},
+};
 
-pub fn get_installed_mods() -> Vec<()> {
-    todo!()
+/// A mod found within the mods directory, identified by its `manifest.json`
+#[derive(Debug)]
+pub struct InstalledMod {
+    pub dir: PathBuf,
+    pub metadata: ModManifest,
+    pub namespaced: NamespacedPackage,
+    /// `false` if the mod lives in the disabled mods folder
+    pub enabled: bool,
+}
+
+/// Walks the mods directory and the disabled mods folder, collecting every folder which contains a
+/// valid mod manifest
+pub fn get_installed_mods(program_state: &ProgramState) -> anyhow::Result<Vec<InstalledMod>> {
+    let mut installed_mods = Vec::new();
+    let mut dirs = VecDeque::new();
+
+    for (root, enabled) in [
+        (program_state.mods_dir.clone(), true),
+        (program_state.disabled_dir(), false),
+    ] {
+        if std::fs::exists(&root)? {
+            dirs.push_back((root, enabled));
+        }
+    }
+
+    while let Some((dir, enabled)) = dirs.pop_front() {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let metadata = std::fs::metadata(entry.path())?;
+
+            if metadata.is_dir() {
+                dirs.push_back((entry.path(), enabled));
+            } else if entry.path().extension().unwrap_or_default() == "json" {
+                let file_str = std::fs::read_to_string(entry.path())?;
+
+                let parent_folder = entry
+                    .path()
+                    .parent()
+                    .ok_or(anyhow!("Unable to get parent folder for installed mod"))?
+                    .to_path_buf();
+
+                let folder_name = parent_folder
+                    .iter()
+                    .next_back()
+                    .ok_or(anyhow!("invalid dir"))?
+                    .to_str()
+                    .ok_or(anyhow!("Invalid characters in dir name"))?;
+
+                let mut splits = folder_name.split('-');
+                let namespace = splits.next().ok_or(anyhow!("Invalid directory name"))?;
+                let name = splits.next().ok_or(anyhow!("Invalid directory name"))?;
+
+                if let Ok(mod_manifest) = serde_json::from_str::<ModManifest>(&file_str) {
+                    installed_mods.push(InstalledMod {
+                        metadata: mod_manifest,
+                        namespaced: NamespacedPackage::new(namespace, name),
+                        dir: parent_folder,
+                        enabled,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(installed_mods)
+}
+
+/// Computes the hex encoded sha256 hash of some bytes
+pub fn hash_bytes(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Lists every file below `dir`, keyed by its path relative to `dir` using `/` as the separator
+pub fn walk_files(dir: &Path) -> anyhow::Result<BTreeMap<String, PathBuf>> {
+    let mut files = BTreeMap::new();
+    let mut dirs = VecDeque::new();
+
+    if !std::fs::exists(dir)? {
+        return Ok(files);
+    }
+    dirs.push_back(dir.to_path_buf());
+
+    while let Some(current) = dirs.pop_front() {
+        for entry in std::fs::read_dir(current)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                dirs.push_back(entry.path());
+                continue;
+            }
+
+            let relative = entry.path().strip_prefix(dir)?.to_path_buf();
+            files.insert(relative_key(&relative)?, entry.path());
+        }
+    }
+
+    Ok(files)
+}
+
+/// Hashes every file below `dir`, keyed by its path relative to `dir` using `/` as the separator
+pub fn hash_dir(dir: &Path) -> anyhow::Result<BTreeMap<String, String>> {
+    walk_files(dir)?
+        .into_iter()
+        .map(|(key, path)| Ok((key, hash_bytes(&std::fs::read(path)?))))
+        .collect()
+}
+
+/// Converts a relative path into the platform independent form stored in the lockfile
+pub fn relative_key(path: &Path) -> anyhow::Result<String> {
+    Ok(path
+        .iter()
+        .map(|part| {
+            part.to_str().ok_or(anyhow!(
+                "Invalid characters in file name {}",
+                path.display()
+            ))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?
+        .join("/"))
+}
+
+/// A directory for a single test to write files into, removed again once the test ends, even when
+/// an assertion fails
+#[cfg(test)]
+pub struct TestDir(PathBuf);
+
+#[cfg(test)]
+impl TestDir {
+    /// Creates an empty directory named after the test, so tests running in parallel never share one
+    pub fn new(test_name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!(
+            "thundermods-test-{}-{}",
+            std::process::id(),
+            test_name
+        ));
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+
+    pub fn join(&self, file_name: &str) -> PathBuf {
+        self.0.join(file_name)
+    }
+}
+
+#[cfg(test)]
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
 }
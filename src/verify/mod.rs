@@ -0,0 +1,140 @@
+use crate::{ProgramState, install, prelude::*, utils};
+use anyhow::anyhow;
+use colored::Colorize;
+
+/// The differences between an installed package and the files recorded when it was installed
+#[derive(Default)]
+struct VerifyReport {
+    modified: Vec<String>,
+    missing: Vec<String>,
+    extra: Vec<String>,
+}
+
+impl VerifyReport {
+    fn is_clean(&self) -> bool {
+        self.modified.is_empty() && self.missing.is_empty() && self.extra.is_empty()
+    }
+}
+
+fn verify_package(
+    program_state: &ProgramState,
+    namespaced: &NamespacedPackage,
+    locked: &LockedPackage,
+) -> anyhow::Result<VerifyReport> {
+    let install_dir = program_state.install_dir(namespaced);
+    let on_disk = utils::hash_dir(&install_dir)?;
+    let mut report = VerifyReport::default();
+
+    for (file, hash) in &locked.files {
+        match on_disk.get(file) {
+            Some(disk_hash) if disk_hash != hash => report.modified.push(file.clone()),
+            Some(_) => {}
+            None => report.missing.push(file.clone()),
+        }
+    }
+
+    report.extra = on_disk
+        .keys()
+        .filter(|file| !locked.files.contains_key(*file))
+        .cloned()
+        .collect();
+
+    Ok(report)
+}
+
+/// Restores a package's install folder to exactly the contents of its original archive
+async fn repair_package(
+    program_state: &ProgramState,
+    api: &thunderstore::Client,
+    namespaced: &NamespacedPackage,
+    locked: &LockedPackage,
+    report: &VerifyReport,
+) -> anyhow::Result<()> {
+    let install_dir = program_state.install_dir(namespaced);
+    let ident = thunderstore::VersionIdent::new(
+        namespaced.namespace(),
+        namespaced.name(),
+        locked.version.to_string(),
+    );
+
+    let files = install::read_archive(&install::fetch_archive(api, &ident).await?)?;
+
+    for file in report.modified.iter().chain(&report.missing) {
+        let contents = files.get(file).ok_or(anyhow!(
+            "{} is missing from the archive for {}",
+            file,
+            ident
+        ))?;
+
+        if utils::hash_bytes(contents) != locked.files[file] {
+            return Err(anyhow!(
+                "The archive for {} does not match the hashes recorded at install",
+                ident
+            ));
+        }
+
+        install::write_archive_file(&install_dir, file, contents)?;
+    }
+
+    for file in &report.extra {
+        std::fs::remove_file(install_dir.join(file))?;
+    }
+
+    Ok(())
+}
+
+pub async fn perform_verify(
+    program_state: &ProgramState,
+    api: &thunderstore::Client,
+    mod_name: Option<NamespacedPackage>,
+    repair: bool,
+) -> anyhow::Result<()> {
+    let mut to_verify = match &mod_name {
+        Some(mod_name) => {
+            let locked = program_state
+                .lockfile
+                .get(mod_name)
+                .ok_or(anyhow!("{} was not installed by this program", mod_name))?;
+            vec![(mod_name, locked)]
+        }
+        None => program_state.lockfile.iter().collect(),
+    };
+    to_verify.sort_by_key(|(namespaced, _)| *namespaced);
+
+    let mut unresolved = 0;
+    for (namespaced, locked) in to_verify {
+        let report = verify_package(program_state, namespaced, locked)?;
+
+        if report.is_clean() {
+            println!("{} {}", "OK".green(), namespaced);
+            continue;
+        }
+
+        println!("{} {}", "FAILED".red(), namespaced);
+        for file in &report.modified {
+            println!("  {} {}", "modified:".yellow(), file);
+        }
+        for file in &report.missing {
+            println!("  {} {}", "missing: ".red(), file);
+        }
+        for file in &report.extra {
+            println!("  {} {}", "extra:   ".cyan(), file);
+        }
+
+        if repair {
+            repair_package(program_state, api, namespaced, locked, &report).await?;
+            println!("  {}", "repaired".green());
+        } else {
+            unresolved += 1;
+        }
+    }
+
+    if unresolved > 0 {
+        return Err(anyhow!(
+            "{} mod(s) failed verification. Run with --repair to restore the original files",
+            unresolved
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,421 @@
+use crate::{ProgramState, prelude::*};
+use chrono::{DateTime, Local};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::PathBuf,
+};
+
+/// A package which was published since the previous refresh
+#[derive(Serialize, Deserialize)]
+struct NewPackage {
+    package: NamespacedPackage,
+    version: semver::Version,
+}
+
+/// A package whose latest version changed since the previous refresh
+#[derive(Serialize, Deserialize)]
+struct NewVersion {
+    package: NamespacedPackage,
+    from: semver::Version,
+    to: semver::Version,
+}
+
+/// The changes to the Thunderstore index between two refreshes of the package cache
+#[derive(Serialize, Deserialize)]
+pub struct WhatsNew {
+    /// When the replaced index was fetched
+    since: Option<DateTime<Local>>,
+    /// When the new index was fetched
+    until: DateTime<Local>,
+    new_packages: Vec<NewPackage>,
+    new_versions: Vec<NewVersion>,
+    deprecated: Vec<NamespacedPackage>,
+    removed: Vec<NamespacedPackage>,
+    /// Whether the changes have been shown. Until they are, the changes of later refreshes are
+    /// added to them.
+    #[serde(default)]
+    reported: bool,
+}
+
+/// The newest published version of a package
+fn latest_version(package: &SearchablePackage) -> Option<&semver::Version> {
+    package.versions.iter().map(|version| &version.number).max()
+}
+
+impl WhatsNew {
+    /// Compares the index which is being replaced with the one replacing it
+    pub fn between(
+        previous: &HashMap<NamespacedPackage, SearchablePackage>,
+        current: &HashMap<NamespacedPackage, SearchablePackage>,
+        since: Option<DateTime<Local>>,
+        until: DateTime<Local>,
+    ) -> Self {
+        let mut changes = Self {
+            since,
+            until,
+            new_packages: Vec::new(),
+            new_versions: Vec::new(),
+            deprecated: Vec::new(),
+            removed: Vec::new(),
+            reported: false,
+        };
+
+        for (namespaced, package) in current {
+            let Some(old) = previous.get(namespaced) else {
+                if let Some(version) = latest_version(package) {
+                    changes.new_packages.push(NewPackage {
+                        package: namespaced.clone(),
+                        version: version.clone(),
+                    });
+                }
+                continue;
+            };
+
+            if let (Some(from), Some(to)) = (latest_version(old), latest_version(package))
+                && to > from
+            {
+                changes.new_versions.push(NewVersion {
+                    package: namespaced.clone(),
+                    from: from.clone(),
+                    to: to.clone(),
+                });
+            }
+            if package.is_deprecated && !old.is_deprecated {
+                changes.deprecated.push(namespaced.clone());
+            }
+        }
+        changes.removed = previous
+            .keys()
+            .filter(|namespaced| !current.contains_key(namespaced))
+            .cloned()
+            .collect();
+
+        changes
+            .new_packages
+            .sort_by(|a, b| a.package.cmp(&b.package));
+        changes
+            .new_versions
+            .sort_by(|a, b| a.package.cmp(&b.package));
+        changes.deprecated.sort();
+        changes.removed.sort();
+        changes
+    }
+
+    /// Adds the changes of a later refresh to these, as if both refreshes had been one. A package
+    /// which was published and removed again in between is left out altogether.
+    fn merge(self, later: Self) -> Self {
+        let mut new_packages = self
+            .new_packages
+            .into_iter()
+            .map(|new_package| (new_package.package, new_package.version))
+            .collect::<BTreeMap<_, _>>();
+        let mut new_versions = self
+            .new_versions
+            .into_iter()
+            .map(|new_version| (new_version.package, (new_version.from, new_version.to)))
+            .collect::<BTreeMap<_, _>>();
+        let mut removed = self.removed.into_iter().collect::<BTreeSet<_>>();
+
+        for new_version in later.new_versions {
+            if let Some(version) = new_packages.get_mut(&new_version.package) {
+                *version = new_version.to;
+                continue;
+            }
+            new_versions
+                .entry(new_version.package)
+                .and_modify(|(_, to)| *to = new_version.to.clone())
+                .or_insert((new_version.from, new_version.to));
+        }
+        for new_package in later.new_packages {
+            removed.remove(&new_package.package);
+            new_packages.insert(new_package.package, new_package.version);
+        }
+        for namespaced in later.removed {
+            new_versions.remove(&namespaced);
+            if new_packages.remove(&namespaced).is_none() {
+                removed.insert(namespaced);
+            }
+        }
+
+        let mut deprecated = self.deprecated;
+        deprecated.extend(later.deprecated);
+        deprecated.sort();
+        deprecated.dedup();
+        deprecated.retain(|namespaced| !removed.contains(namespaced));
+
+        Self {
+            since: self.since,
+            until: later.until,
+            new_packages: new_packages
+                .into_iter()
+                .map(|(package, version)| NewPackage { package, version })
+                .collect(),
+            new_versions: new_versions
+                .into_iter()
+                .map(|(package, (from, to))| NewVersion { package, from, to })
+                .collect(),
+            deprecated,
+            removed: removed.into_iter().collect(),
+            reported: false,
+        }
+    }
+
+    /// Keeps the changes of a refresh, adding them to the changes which have not been shown yet
+    pub fn record(self, managed_game: &str) -> anyhow::Result<()> {
+        let changes = match Self::load(managed_game) {
+            Ok(Some(unreported)) if !unreported.reported => unreported.merge(self),
+            _ => self,
+        };
+        changes.save(managed_game)
+    }
+
+    /// Where the changes recorded from refreshes of a game's package cache are kept
+    fn path(managed_game: &str) -> PathBuf {
+        CACHE_DIR
+            .join("whats_new")
+            .join(format!("{}.json", managed_game))
+    }
+
+    /// Loads the recorded changes, `None` if the cache has not been refreshed over an older one yet
+    pub fn load(managed_game: &str) -> anyhow::Result<Option<Self>> {
+        let path = Self::path(managed_game);
+        if !std::fs::exists(&path)? {
+            return Ok(None);
+        }
+        let reader = BufReader::new(File::open(path)?);
+        Ok(Some(serde_json::from_reader(reader)?))
+    }
+
+    pub fn save(&self, managed_game: &str) -> anyhow::Result<()> {
+        let path = Self::path(managed_game);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    /// Prints the changes, leaving out new versions of packages the session neither installed
+    /// nor requires. Installed and required packages are marked as such.
+    pub fn print(&self, program_state: &ProgramState) {
+        let in_use = |namespaced: &NamespacedPackage| {
+            if program_state.lockfile.get(namespaced).is_some() {
+                format!(" {}", "[installed]".green())
+            } else if program_state.requirements.get(namespaced).is_some() {
+                format!(" {}", "[required]".green())
+            } else {
+                String::new()
+            }
+        };
+        let heading = |title: &str, count: usize| println!("\n{} ({})", title.bold(), count);
+
+        println!(
+            "Changes on Thunderstore from {} to {}",
+            self.since
+                .map(|since| since.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or("N/A".into()),
+            self.until.format("%Y-%m-%d %H:%M:%S")
+        );
+
+        let new_versions = self
+            .new_versions
+            .iter()
+            .filter(|new_version| {
+                program_state.lockfile.get(&new_version.package).is_some()
+                    || program_state
+                        .requirements
+                        .get(&new_version.package)
+                        .is_some()
+            })
+            .collect::<Vec<_>>();
+        if self.new_packages.is_empty()
+            && new_versions.is_empty()
+            && self.deprecated.is_empty()
+            && self.removed.is_empty()
+        {
+            println!("Nothing has changed");
+            return;
+        }
+
+        if !new_versions.is_empty() {
+            heading("New versions of your mods", new_versions.len());
+            for new_version in new_versions {
+                println!(
+                    "  {}: {} -> {}{}",
+                    new_version.package,
+                    new_version.from,
+                    new_version.to.to_string().green(),
+                    in_use(&new_version.package)
+                );
+            }
+        }
+
+        if !self.deprecated.is_empty() {
+            heading("Newly deprecated", self.deprecated.len());
+            for namespaced in &self.deprecated {
+                println!("  {}{}", namespaced.to_string().red(), in_use(namespaced));
+            }
+        }
+
+        if !self.removed.is_empty() {
+            heading("Removed from Thunderstore", self.removed.len());
+            for namespaced in &self.removed {
+                println!("  {}{}", namespaced.to_string().red(), in_use(namespaced));
+            }
+        }
+
+        if !self.new_packages.is_empty() {
+            heading("New packages", self.new_packages.len());
+            for new_package in &self.new_packages {
+                let description = program_state
+                    .packages
+                    .get(&new_package.package)
+                    .and_then(|package| {
+                        crate::info::versions_newest_first(package).first().copied()
+                    })
+                    .map(|version| format!(" - {}", version.description).dimmed().to_string())
+                    .unwrap_or_default();
+                println!(
+                    "  {} {}{}",
+                    new_package.package, new_package.version, description
+                );
+            }
+        }
+    }
+}
+
+const NOTHING_RECORDED: &str =
+    "No changes have been recorded yet. They are collected each time the mod cache is refreshed";
+
+/// Prints the recorded changes, which starts a new set of changes with the next refresh
+fn report(program_state: &ProgramState) -> anyhow::Result<()> {
+    let Some(mut changes) = WhatsNew::load(&program_state.managed_game)? else {
+        println!("{}", NOTHING_RECORDED);
+        return Ok(());
+    };
+    changes.print(program_state);
+    if !changes.reported {
+        changes.reported = true;
+        changes.save(&program_state.managed_game)?;
+    }
+    Ok(())
+}
+
+/// Shows what changed on Thunderstore since the changes were last shown
+pub fn view(program_state: &ProgramState) -> anyhow::Result<()> {
+    clearscreen::clear()?;
+    report(program_state)?;
+    println!();
+
+    inquire::Confirm::new("Press enter to continue")
+        .with_default(true)
+        .prompt_skippable()?;
+    Ok(())
+}
+
+/// Prints what changed on Thunderstore since the changes were last shown, refreshing the package
+/// cache first if asked to. Nothing but the changes is printed, as the output is often mailed by
+/// cron.
+pub async fn perform_whats_new(
+    mut program_state: ProgramState,
+    api: &thunderstore::Client,
+    update_cache: bool,
+) -> anyhow::Result<()> {
+    if update_cache || program_state.packages.is_empty() {
+        program_state.refresh_packages(api).await?;
+    }
+
+    report(&program_state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str) -> NamespacedPackage {
+        NamespacedPackage::value_parser(name).unwrap()
+    }
+
+    fn version(version: &str) -> semver::Version {
+        semver::Version::parse(version).unwrap()
+    }
+
+    fn changes(
+        new_packages: &[(&str, &str)],
+        new_versions: &[(&str, &str, &str)],
+        removed: &[&str],
+    ) -> WhatsNew {
+        WhatsNew {
+            since: None,
+            until: Local::now(),
+            new_packages: new_packages
+                .iter()
+                .map(|(name, number)| NewPackage {
+                    package: package(name),
+                    version: version(number),
+                })
+                .collect(),
+            new_versions: new_versions
+                .iter()
+                .map(|(name, from, to)| NewVersion {
+                    package: package(name),
+                    from: version(from),
+                    to: version(to),
+                })
+                .collect(),
+            deprecated: Vec::new(),
+            removed: removed.iter().map(|name| package(name)).collect(),
+            reported: false,
+        }
+    }
+
+    #[test]
+    fn merged_changes_span_both_refreshes() {
+        let earlier = changes(
+            &[("A/New", "1.0.0"), ("A/Gone", "1.0.0")],
+            &[("A/Mod", "1.0.0", "1.1.0")],
+            &[],
+        );
+        let later = changes(
+            &[("A/Newer", "0.1.0")],
+            &[("A/Mod", "1.1.0", "1.2.0"), ("A/New", "1.0.0", "1.0.1")],
+            &["A/Gone", "A/Old"],
+        );
+
+        let merged = earlier.merge(later);
+
+        let new_packages = merged
+            .new_packages
+            .iter()
+            .map(|new| (new.package.to_string(), new.version.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            new_packages,
+            [
+                ("A/New".to_owned(), "1.0.1".to_owned()),
+                ("A/Newer".to_owned(), "0.1.0".to_owned())
+            ]
+        );
+        let new_versions = merged
+            .new_versions
+            .iter()
+            .map(|new| {
+                (
+                    new.package.to_string(),
+                    new.from.to_string(),
+                    new.to.to_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            new_versions,
+            [("A/Mod".to_owned(), "1.0.0".to_owned(), "1.2.0".to_owned())]
+        );
+        assert_eq!(merged.removed, [package("A/Old")]);
+    }
+}
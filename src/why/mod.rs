@@ -0,0 +1,197 @@
+use crate::{ProgramState, prelude::*, utils};
+use colored::Colorize;
+use std::collections::{HashMap, VecDeque};
+use thunderstore::VersionIdent;
+
+/// A step of a dependency chain: the package depended on, and the minimum version of it which the
+/// previous package of the chain asks for
+type Link = (NamespacedPackage, semver::Version);
+
+/// Finds the shortest path through the installed mods' dependencies which leads from `root` to
+/// `target`, searching breadth first so that packages shared by many chains are only visited once
+fn shortest_chain(
+    dependencies: &HashMap<NamespacedPackage, Vec<VersionIdent>>,
+    root: &NamespacedPackage,
+    target: &NamespacedPackage,
+) -> Option<Vec<Link>> {
+    // The package each visited package was first reached from, and the version it asked for
+    let mut reached_from =
+        HashMap::<NamespacedPackage, (NamespacedPackage, semver::Version)>::new();
+    let mut queue = VecDeque::from([root.clone()]);
+
+    while let Some(current) = queue.pop_front() {
+        for dependency in dependencies.get(&current).into_iter().flatten() {
+            let namespaced = NamespacedPackage::from(dependency);
+            if namespaced == *root || reached_from.contains_key(&namespaced) {
+                continue;
+            }
+            reached_from.insert(
+                namespaced.clone(),
+                (current.clone(), dependency.parsed_version()),
+            );
+
+            if namespaced == *target {
+                let mut chain = Vec::new();
+                let mut step = namespaced;
+                while let Some((previous, minimum)) = reached_from.remove(&step) {
+                    chain.push((step, minimum));
+                    step = previous;
+                }
+                chain.reverse();
+                return Some(chain);
+            }
+            queue.push_back(namespaced);
+        }
+    }
+
+    None
+}
+
+/// Prints the shortest dependency chain from each explicit requirement which leads to a mod
+pub fn perform_why(program_state: &ProgramState, target: &NamespacedPackage) -> anyhow::Result<()> {
+    let installed = utils::get_installed_mods(program_state)?;
+    let versions = installed
+        .iter()
+        .map(|installed| {
+            (
+                installed.namespaced.clone(),
+                installed.metadata.version.clone(),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+    let dependencies = installed
+        .into_iter()
+        .map(|installed| (installed.namespaced, installed.metadata.dependencies))
+        .collect::<HashMap<_, _>>();
+
+    let installed_version = |namespaced: &NamespacedPackage| {
+        versions
+            .get(namespaced)
+            .map(|version| format!("installed {}", version).green())
+            .unwrap_or("not installed".red())
+    };
+
+    if !versions.contains_key(target) {
+        println!("{} is not installed", target);
+    }
+
+    let mut explained = false;
+    if let Some(requirement) = program_state.requirements.get(target) {
+        println!(
+            "{} is explicitly required as {} ({})",
+            target.to_string().cyan(),
+            requirement.version,
+            installed_version(target)
+        );
+        explained = true;
+    }
+
+    let mut roots = program_state
+        .requirements
+        .iter()
+        .filter(|(namespaced, _)| *namespaced != target)
+        .collect::<Vec<_>>();
+    roots.sort_by_key(|(namespaced, _)| *namespaced);
+
+    for (root, requirement) in roots {
+        if let Some(chain) = shortest_chain(&dependencies, root, target) {
+            if explained {
+                println!();
+            }
+            println!(
+                "{} required as {} ({})",
+                root.to_string().cyan(),
+                requirement.version,
+                installed_version(root)
+            );
+            for (depth, (namespaced, minimum)) in chain.iter().enumerate() {
+                println!(
+                    "{}└─ {} >= {} ({})",
+                    "   ".repeat(depth),
+                    namespaced,
+                    minimum,
+                    installed_version(namespaced)
+                );
+            }
+            explained = true;
+        }
+    }
+
+    if !explained && versions.contains_key(target) {
+        println!(
+            "{} is installed, but is not needed by any of the requirements",
+            target
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str) -> NamespacedPackage {
+        NamespacedPackage::value_parser(name).unwrap()
+    }
+
+    /// Builds a dependency graph where every package depends on the given packages at 1.0.0
+    fn graph(edges: &[(&str, &[&str])]) -> HashMap<NamespacedPackage, Vec<VersionIdent>> {
+        edges
+            .iter()
+            .map(|(from, to)| {
+                let to = to
+                    .iter()
+                    .map(|to| {
+                        let to = package(to);
+                        VersionIdent::new(to.namespace(), to.name(), "1.0.0")
+                    })
+                    .collect();
+                (package(from), to)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn the_shortest_chain_is_found() {
+        let dependencies = graph(&[
+            ("A/Root", &["A/Long", "A/Short"]),
+            ("A/Long", &["A/Longer"]),
+            ("A/Longer", &["A/Target"]),
+            ("A/Short", &["A/Target"]),
+        ]);
+
+        let chain = shortest_chain(&dependencies, &package("A/Root"), &package("A/Target"))
+            .unwrap()
+            .into_iter()
+            .map(|(namespaced, _)| namespaced.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(chain, ["A/Short", "A/Target"]);
+    }
+
+    #[test]
+    fn diamonds_are_searched_quickly() {
+        // Every layer depends on both packages of the next one, so there are 2^40 distinct paths
+        let layers = (0..40)
+            .map(|layer| [format!("L/Left{layer}"), format!("L/Right{layer}")])
+            .collect::<Vec<_>>();
+        let mut dependencies = graph(&[]);
+        let mut depend_on_layer = |from: &str, layer: &[String]| {
+            let to = layer
+                .iter()
+                .map(|to| VersionIdent::new("L", &to[2..], "1.0.0"))
+                .collect();
+            dependencies.insert(package(from), to);
+        };
+        depend_on_layer("L/Root", &layers[0]);
+        for pair in layers.windows(2) {
+            for from in &pair[0] {
+                depend_on_layer(from, &pair[1]);
+            }
+        }
+
+        let chain = shortest_chain(&dependencies, &package("L/Root"), &package("L/Right39"));
+        assert_eq!(chain.unwrap().len(), 40);
+        assert!(shortest_chain(&dependencies, &package("L/Right39"), &package("L/Root")).is_none());
+    }
+}